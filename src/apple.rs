@@ -0,0 +1,165 @@
+use std::path::Path;
+
+use plist::{Dictionary, Value};
+use uuid::Uuid;
+
+use crate::{EapMethod, GenerationError, Phase2, Wifi, WifiMethod};
+
+impl Wifi {
+    /// Write an (unsigned) iOS/macOS configuration profile that installs this network,
+    /// i.e. a `.mobileconfig` containing a single `com.apple.wifi.managed` payload.
+    ///
+    /// A raw `T` value set with [`Wifi::with_raw_auth_type`] can't be represented and is ignored.
+    /// Apple only supports a subset of the EAP methods, profiles with other methods won't install.
+    /// For enterprise networks the password is only stored as the user's EAP password.
+    pub fn export_mobileconfig(&self, path: &Path) -> Result<(), GenerationError> {
+        let mut wifi = Dictionary::new();
+        add_payload_header(&mut wifi, "com.apple.wifi.managed", &self.ssid);
+        wifi.insert("SSID_STR".into(), self.ssid.clone().into());
+        wifi.insert("HIDDEN_NETWORK".into(), self.hidden.into());
+        wifi.insert("AutoJoin".into(), true.into());
+
+        let encryption = match self.kind {
+            None => "Any",
+            Some(WifiMethod::NoPass | WifiMethod::Owe) => "None",
+            Some(WifiMethod::Wep) => "WEP",
+            Some(WifiMethod::Wpa) => "WPA",
+            Some(WifiMethod::Wpa2 | WifiMethod::Wpa2Enterprise) => "WPA2",
+            Some(WifiMethod::Wpa3) => "WPA3",
+        };
+        wifi.insert("EncryptionType".into(), encryption.into());
+
+        if self.is_enterprise() || self.eap_method.is_some() {
+            // the password belongs to the user, it only goes into the EAP configuration
+            wifi.insert("EAPClientConfiguration".into(), self.eap_client_configuration().into());
+        } else if let Some(password) = &self.password {
+            wifi.insert("Password".into(), password.clone().into());
+        } else if let Some(psk) = &self.psk {
+            let hex: String = psk.iter().map(|b| format!("{b:02x}")).collect();
+            wifi.insert("Password".into(), hex.into());
+        }
+
+        let mut profile = Dictionary::new();
+        add_payload_header(&mut profile, "Configuration", &format!("Wi-Fi {}", self.ssid));
+        profile.insert("PayloadContent".into(), vec![Value::from(wifi)].into());
+
+        Value::from(profile).to_file_xml(path)?;
+
+        Ok(())
+    }
+
+    fn eap_client_configuration(&self) -> Dictionary {
+        let mut eap = Dictionary::new();
+
+        if let Some(method) = &self.eap_method {
+            // IANA EAP method type numbers
+            let eap_type = match method {
+                EapMethod::Tls => 13,
+                EapMethod::Sim => 18,
+                EapMethod::Ttls => 21,
+                EapMethod::Aka => 23,
+                EapMethod::Peap => 25,
+                EapMethod::AkaPrime => 50,
+                EapMethod::Pwd => 52,
+            };
+            eap.insert("AcceptEAPTypes".into(), vec![Value::from(eap_type)].into());
+        }
+
+        if let Some(phase2) = &self.phase2 {
+            let inner = match phase2 {
+                Phase2::Pap => "PAP",
+                Phase2::MsChap => "MSCHAP",
+                Phase2::MsChapV2 => "MSCHAPv2",
+                Phase2::Gtc | Phase2::Sim | Phase2::Aka | Phase2::AkaPrime => "EAP",
+            };
+            eap.insert("TTLSInnerAuthentication".into(), inner.into());
+        }
+
+        if let Some(identity) = &self.identity {
+            eap.insert("UserName".into(), identity.clone().into());
+        }
+
+        if let Some(anonymous_identity) = &self.anonymous_identity {
+            eap.insert("OuterIdentity".into(), anonymous_identity.clone().into());
+        }
+
+        if let Some(password) = &self.password {
+            eap.insert("UserPassword".into(), password.clone().into());
+        }
+
+        eap
+    }
+}
+
+/// The keys every profile payload needs, identified by a fresh UUID
+fn add_payload_header(payload: &mut Dictionary, payload_type: &str, display_name: &str) {
+    let uuid = Uuid::new_v4().hyphenated().to_string().to_uppercase();
+    payload.insert("PayloadType".into(), payload_type.into());
+    payload.insert("PayloadVersion".into(), 1.into());
+    payload.insert(
+        "PayloadIdentifier".into(),
+        format!("wifi-qr-code-generator.{uuid}").into(),
+    );
+    payload.insert("PayloadUUID".into(), uuid.into());
+    payload.insert("PayloadDisplayName".into(), display_name.into());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The wifi payload of the exported profile
+    fn export(wifi: &Wifi) -> Dictionary {
+        let path = std::env::temp_dir().join(format!(
+            "wifi-qr-{}-{}.mobileconfig",
+            wifi.ssid,
+            std::process::id()
+        ));
+        wifi.export_mobileconfig(&path).unwrap();
+        let profile = Value::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let profile = profile.into_dictionary().unwrap();
+        assert_eq!(profile["PayloadType"].as_string(), Some("Configuration"));
+        let content = profile["PayloadContent"].as_array().unwrap();
+        assert_eq!(content.len(), 1);
+        content[0].as_dictionary().unwrap().clone()
+    }
+
+    #[test]
+    fn wpa_profile() {
+        let wifi = Wifi::new("Home".to_string())
+            .with_method(Some(WifiMethod::Wpa2))
+            .with_password(Some("password".to_string()));
+        let payload = export(&wifi);
+
+        assert_eq!(
+            payload["PayloadType"].as_string(),
+            Some("com.apple.wifi.managed")
+        );
+        assert_eq!(payload["SSID_STR"].as_string(), Some("Home"));
+        assert_eq!(payload["EncryptionType"].as_string(), Some("WPA2"));
+        assert_eq!(payload["Password"].as_string(), Some("password"));
+        assert!(!payload.contains_key("EAPClientConfiguration"));
+    }
+
+    #[test]
+    fn enterprise_profile() {
+        let wifi = Wifi::new("Office".to_string())
+            .with_method(Some(WifiMethod::Wpa2Enterprise))
+            .with_eap_method(Some(EapMethod::Peap))
+            .with_identity(Some("user".to_string()))
+            .with_password(Some("password".to_string()));
+        let payload = export(&wifi);
+
+        assert_eq!(payload["SSID_STR"].as_string(), Some("Office"));
+        assert_eq!(payload["EncryptionType"].as_string(), Some("WPA2"));
+        assert!(!payload.contains_key("Password"));
+
+        let eap = payload["EAPClientConfiguration"].as_dictionary().unwrap();
+        assert_eq!(eap["UserName"].as_string(), Some("user"));
+        assert_eq!(eap["UserPassword"].as_string(), Some("password"));
+        let types = eap["AcceptEAPTypes"].as_array().unwrap();
+        assert_eq!(types[0].as_unsigned_integer(), Some(25));
+    }
+}
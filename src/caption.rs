@@ -0,0 +1,166 @@
+//! Text below the code, drawn with a bundled bitmap font
+
+use image::{ImageBuffer, Luma};
+
+const GLYPH_WIDTH: u32 = 6;
+const GLYPH_HEIGHT: u32 = 10;
+
+/// The printable ASCII characters of the public domain X11 misc-fixed 6x10 font,
+/// one byte per row with the leftmost pixel in bit 5
+const FONT: [[u8; GLYPH_HEIGHT as usize]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // space
+    [0x00, 0x08, 0x08, 0x08, 0x08, 0x08, 0x00, 0x08, 0x00, 0x00], // `!`
+    [0x00, 0x14, 0x14, 0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // `"`
+    [0x00, 0x14, 0x14, 0x3e, 0x14, 0x3e, 0x14, 0x14, 0x00, 0x00], // `#`
+    [0x00, 0x08, 0x1c, 0x28, 0x1c, 0x0a, 0x1c, 0x08, 0x00, 0x00], // `$`
+    [0x00, 0x12, 0x2a, 0x14, 0x08, 0x14, 0x2a, 0x24, 0x00, 0x00], // `%`
+    [0x00, 0x10, 0x28, 0x28, 0x10, 0x2a, 0x24, 0x1a, 0x00, 0x00], // `&`
+    [0x00, 0x08, 0x08, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // `'`
+    [0x00, 0x04, 0x08, 0x10, 0x10, 0x10, 0x08, 0x04, 0x00, 0x00], // `(`
+    [0x00, 0x10, 0x08, 0x04, 0x04, 0x04, 0x08, 0x10, 0x00, 0x00], // `)`
+    [0x00, 0x00, 0x22, 0x14, 0x3e, 0x14, 0x22, 0x00, 0x00, 0x00], // `*`
+    [0x00, 0x00, 0x08, 0x08, 0x3e, 0x08, 0x08, 0x00, 0x00, 0x00], // `+`
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0c, 0x08, 0x10, 0x00], // `,`
+    [0x00, 0x00, 0x00, 0x00, 0x3e, 0x00, 0x00, 0x00, 0x00, 0x00], // `-`
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x08, 0x1c, 0x08, 0x00], // `.`
+    [0x00, 0x02, 0x02, 0x04, 0x08, 0x10, 0x20, 0x20, 0x00, 0x00], // `/`
+    [0x00, 0x08, 0x14, 0x22, 0x22, 0x22, 0x14, 0x08, 0x00, 0x00], // `0`
+    [0x00, 0x08, 0x18, 0x28, 0x08, 0x08, 0x08, 0x3e, 0x00, 0x00], // `1`
+    [0x00, 0x1c, 0x22, 0x02, 0x0c, 0x10, 0x20, 0x3e, 0x00, 0x00], // `2`
+    [0x00, 0x3e, 0x02, 0x04, 0x0c, 0x02, 0x22, 0x1c, 0x00, 0x00], // `3`
+    [0x00, 0x04, 0x0c, 0x14, 0x24, 0x3e, 0x04, 0x04, 0x00, 0x00], // `4`
+    [0x00, 0x3e, 0x20, 0x2c, 0x32, 0x02, 0x22, 0x1c, 0x00, 0x00], // `5`
+    [0x00, 0x0c, 0x10, 0x20, 0x2c, 0x32, 0x22, 0x1c, 0x00, 0x00], // `6`
+    [0x00, 0x3e, 0x02, 0x04, 0x04, 0x08, 0x10, 0x10, 0x00, 0x00], // `7`
+    [0x00, 0x1c, 0x22, 0x22, 0x1c, 0x22, 0x22, 0x1c, 0x00, 0x00], // `8`
+    [0x00, 0x1c, 0x22, 0x26, 0x1a, 0x02, 0x04, 0x18, 0x00, 0x00], // `9`
+    [0x00, 0x00, 0x08, 0x1c, 0x08, 0x00, 0x08, 0x1c, 0x08, 0x00], // `:`
+    [0x00, 0x00, 0x08, 0x1c, 0x08, 0x00, 0x0c, 0x08, 0x10, 0x00], // `;`
+    [0x00, 0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02, 0x00, 0x00], // `<`
+    [0x00, 0x00, 0x00, 0x3e, 0x00, 0x3e, 0x00, 0x00, 0x00, 0x00], // `=`
+    [0x00, 0x10, 0x08, 0x04, 0x02, 0x04, 0x08, 0x10, 0x00, 0x00], // `>`
+    [0x00, 0x1c, 0x22, 0x04, 0x08, 0x08, 0x00, 0x08, 0x00, 0x00], // `?`
+    [0x00, 0x1c, 0x22, 0x26, 0x2a, 0x2c, 0x20, 0x1c, 0x00, 0x00], // `@`
+    [0x00, 0x08, 0x14, 0x22, 0x22, 0x3e, 0x22, 0x22, 0x00, 0x00], // `A`
+    [0x00, 0x3c, 0x12, 0x12, 0x1c, 0x12, 0x12, 0x3c, 0x00, 0x00], // `B`
+    [0x00, 0x1c, 0x22, 0x20, 0x20, 0x20, 0x22, 0x1c, 0x00, 0x00], // `C`
+    [0x00, 0x3c, 0x12, 0x12, 0x12, 0x12, 0x12, 0x3c, 0x00, 0x00], // `D`
+    [0x00, 0x3e, 0x20, 0x20, 0x3c, 0x20, 0x20, 0x3e, 0x00, 0x00], // `E`
+    [0x00, 0x3e, 0x20, 0x20, 0x3c, 0x20, 0x20, 0x20, 0x00, 0x00], // `F`
+    [0x00, 0x1c, 0x22, 0x20, 0x20, 0x26, 0x22, 0x1c, 0x00, 0x00], // `G`
+    [0x00, 0x22, 0x22, 0x22, 0x3e, 0x22, 0x22, 0x22, 0x00, 0x00], // `H`
+    [0x00, 0x1c, 0x08, 0x08, 0x08, 0x08, 0x08, 0x1c, 0x00, 0x00], // `I`
+    [0x00, 0x0e, 0x04, 0x04, 0x04, 0x04, 0x24, 0x18, 0x00, 0x00], // `J`
+    [0x00, 0x22, 0x24, 0x28, 0x30, 0x28, 0x24, 0x22, 0x00, 0x00], // `K`
+    [0x00, 0x20, 0x20, 0x20, 0x20, 0x20, 0x20, 0x3e, 0x00, 0x00], // `L`
+    [0x00, 0x22, 0x22, 0x36, 0x2a, 0x22, 0x22, 0x22, 0x00, 0x00], // `M`
+    [0x00, 0x22, 0x22, 0x32, 0x2a, 0x26, 0x22, 0x22, 0x00, 0x00], // `N`
+    [0x00, 0x1c, 0x22, 0x22, 0x22, 0x22, 0x22, 0x1c, 0x00, 0x00], // `O`
+    [0x00, 0x3c, 0x22, 0x22, 0x3c, 0x20, 0x20, 0x20, 0x00, 0x00], // `P`
+    [0x00, 0x1c, 0x22, 0x22, 0x22, 0x22, 0x2a, 0x1c, 0x02, 0x00], // `Q`
+    [0x00, 0x3c, 0x22, 0x22, 0x3c, 0x28, 0x24, 0x22, 0x00, 0x00], // `R`
+    [0x00, 0x1c, 0x22, 0x20, 0x1c, 0x02, 0x22, 0x1c, 0x00, 0x00], // `S`
+    [0x00, 0x3e, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08, 0x00, 0x00], // `T`
+    [0x00, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x1c, 0x00, 0x00], // `U`
+    [0x00, 0x22, 0x22, 0x22, 0x14, 0x14, 0x14, 0x08, 0x00, 0x00], // `V`
+    [0x00, 0x22, 0x22, 0x22, 0x2a, 0x2a, 0x36, 0x22, 0x00, 0x00], // `W`
+    [0x00, 0x22, 0x22, 0x14, 0x08, 0x14, 0x22, 0x22, 0x00, 0x00], // `X`
+    [0x00, 0x22, 0x22, 0x14, 0x08, 0x08, 0x08, 0x08, 0x00, 0x00], // `Y`
+    [0x00, 0x3e, 0x02, 0x04, 0x08, 0x10, 0x20, 0x3e, 0x00, 0x00], // `Z`
+    [0x00, 0x1c, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1c, 0x00, 0x00], // `[`
+    [0x00, 0x20, 0x20, 0x10, 0x08, 0x04, 0x02, 0x02, 0x00, 0x00], // `\`
+    [0x00, 0x1c, 0x04, 0x04, 0x04, 0x04, 0x04, 0x1c, 0x00, 0x00], // `]`
+    [0x00, 0x08, 0x14, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // `^`
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3e, 0x00], // `_`
+    [0x08, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // backtick
+    [0x00, 0x00, 0x00, 0x1c, 0x02, 0x1e, 0x22, 0x1e, 0x00, 0x00], // `a`
+    [0x00, 0x20, 0x20, 0x2c, 0x32, 0x22, 0x32, 0x2c, 0x00, 0x00], // `b`
+    [0x00, 0x00, 0x00, 0x1c, 0x22, 0x20, 0x22, 0x1c, 0x00, 0x00], // `c`
+    [0x00, 0x02, 0x02, 0x1a, 0x26, 0x22, 0x26, 0x1a, 0x00, 0x00], // `d`
+    [0x00, 0x00, 0x00, 0x1c, 0x22, 0x3e, 0x20, 0x1c, 0x00, 0x00], // `e`
+    [0x00, 0x0c, 0x12, 0x10, 0x3c, 0x10, 0x10, 0x10, 0x00, 0x00], // `f`
+    [0x00, 0x00, 0x00, 0x1e, 0x22, 0x22, 0x1e, 0x02, 0x22, 0x1c], // `g`
+    [0x00, 0x20, 0x20, 0x2c, 0x32, 0x22, 0x22, 0x22, 0x00, 0x00], // `h`
+    [0x00, 0x08, 0x00, 0x18, 0x08, 0x08, 0x08, 0x1c, 0x00, 0x00], // `i`
+    [0x00, 0x02, 0x00, 0x06, 0x02, 0x02, 0x02, 0x12, 0x12, 0x0c], // `j`
+    [0x00, 0x20, 0x20, 0x22, 0x24, 0x38, 0x24, 0x22, 0x00, 0x00], // `k`
+    [0x00, 0x18, 0x08, 0x08, 0x08, 0x08, 0x08, 0x1c, 0x00, 0x00], // `l`
+    [0x00, 0x00, 0x00, 0x34, 0x2a, 0x2a, 0x2a, 0x22, 0x00, 0x00], // `m`
+    [0x00, 0x00, 0x00, 0x2c, 0x32, 0x22, 0x22, 0x22, 0x00, 0x00], // `n`
+    [0x00, 0x00, 0x00, 0x1c, 0x22, 0x22, 0x22, 0x1c, 0x00, 0x00], // `o`
+    [0x00, 0x00, 0x00, 0x2c, 0x32, 0x22, 0x32, 0x2c, 0x20, 0x20], // `p`
+    [0x00, 0x00, 0x00, 0x1a, 0x26, 0x22, 0x26, 0x1a, 0x02, 0x02], // `q`
+    [0x00, 0x00, 0x00, 0x2c, 0x32, 0x20, 0x20, 0x20, 0x00, 0x00], // `r`
+    [0x00, 0x00, 0x00, 0x1c, 0x20, 0x1c, 0x02, 0x3c, 0x00, 0x00], // `s`
+    [0x00, 0x10, 0x10, 0x3c, 0x10, 0x10, 0x12, 0x0c, 0x00, 0x00], // `t`
+    [0x00, 0x00, 0x00, 0x22, 0x22, 0x22, 0x26, 0x1a, 0x00, 0x00], // `u`
+    [0x00, 0x00, 0x00, 0x22, 0x22, 0x14, 0x14, 0x08, 0x00, 0x00], // `v`
+    [0x00, 0x00, 0x00, 0x22, 0x22, 0x2a, 0x2a, 0x14, 0x00, 0x00], // `w`
+    [0x00, 0x00, 0x00, 0x22, 0x14, 0x08, 0x14, 0x22, 0x00, 0x00], // `x`
+    [0x00, 0x00, 0x00, 0x22, 0x22, 0x26, 0x1a, 0x02, 0x22, 0x1c], // `y`
+    [0x00, 0x00, 0x00, 0x3e, 0x04, 0x08, 0x10, 0x3e, 0x00, 0x00], // `z`
+    [0x00, 0x06, 0x08, 0x04, 0x18, 0x04, 0x08, 0x06, 0x00, 0x00], // `{`
+    [0x00, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08, 0x00, 0x00], // `|`
+    [0x00, 0x18, 0x04, 0x08, 0x06, 0x08, 0x04, 0x18, 0x00, 0x00], // `}`
+    [0x00, 0x12, 0x2a, 0x24, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // `~`
+];
+
+/// Draw `caption` centered into a band `width` pixels wide, black on white like the code
+///
+/// The font is scaled with the module size, down to one pixel per font pixel if that is too wide,
+/// and the caption is cut off with `...` if it still doesn't fit.
+/// Characters outside of printable ASCII are drawn as `?`.
+pub(crate) fn caption_band(
+    caption: &str,
+    width: u32,
+    module_size: u32,
+) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+    text_band(caption, width, module_size / 4)
+}
+
+/// Like [`caption_band`], but with the font scaled up to `max_scale` times its size
+pub(crate) fn text_band(text: &str, width: u32, max_scale: u32) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+    let mut glyphs: Vec<usize> = text.chars().map(glyph_index).collect();
+    let max_glyphs = (width / GLYPH_WIDTH) as usize;
+    if glyphs.len() > max_glyphs {
+        let dots = glyph_index('.');
+        glyphs.truncate(max_glyphs.saturating_sub(3));
+        glyphs.resize(max_glyphs, dots);
+    }
+
+    let text_width = glyphs.len() as u32 * GLYPH_WIDTH;
+    let scale = max_scale
+        .min(width.checked_div(text_width).unwrap_or(u32::MAX))
+        .max(1);
+    // the same padding above and below the text
+    let padding = 2 * scale;
+
+    let mut band = ImageBuffer::from_pixel(width, GLYPH_HEIGHT * scale + 2 * padding, Luma([255]));
+    let left = (width - text_width * scale) / 2;
+    for (index, glyph) in glyphs.into_iter().enumerate() {
+        let glyph_left = left + index as u32 * GLYPH_WIDTH * scale;
+        for (row, bits) in FONT[glyph].iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                if bits >> (GLYPH_WIDTH - 1 - column) & 1 == 1 {
+                    for dy in 0..scale {
+                        for dx in 0..scale {
+                            band.put_pixel(
+                                glyph_left + column * scale + dx,
+                                padding + row as u32 * scale + dy,
+                                Luma([0]),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+    band
+}
+
+fn glyph_index(c: char) -> usize {
+    match c {
+        ' '..='~' => c as usize - ' ' as usize,
+        _ => '?' as usize - ' ' as usize,
+    }
+}
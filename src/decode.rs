@@ -0,0 +1,65 @@
+//! Reading a network back from a picture of its QR-Code
+
+use std::path::Path;
+
+use crate::parse::payload_fields;
+use crate::{GenerationError, Wifi};
+
+impl Wifi {
+    /// Decode the QR-Code in the image at `path`, e.g. a scanned sticker,
+    /// and parse its payload like [`Wifi::from_str`](std::str::FromStr)
+    ///
+    /// Fails with [`GenerationError::NoCodeFound`] if no code can be read,
+    /// with [`GenerationError::NotWifiPayload`] if the code holds something else
+    /// and with [`GenerationError::InvalidPayload`] if the payload is malformed.
+    pub fn from_image(path: &Path) -> Result<Wifi, GenerationError> {
+        let image = image::open(path)?.into_luma8();
+        let (width, height) = image.dimensions();
+        let mut prepared =
+            rqrr::PreparedImage::prepare_from_greyscale(width as usize, height as usize, |x, y| {
+                image.get_pixel(x as u32, y as u32).0[0]
+            });
+
+        let (_, payload) = prepared
+            .detect_grids()
+            .into_iter()
+            .find_map(|grid| grid.decode().ok())
+            .ok_or(GenerationError::NoCodeFound)?;
+        if payload_fields(&payload).is_none() {
+            return Err(GenerationError::NotWifiPayload);
+        }
+        payload.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DppConfig, WifiMethod};
+
+    #[test]
+    fn generated_code_round_trip() {
+        let path = std::env::temp_dir().join(format!("wifi-qr-decode-{}.png", std::process::id()));
+        let wifi = Wifi::new("Café; \"Bar\"".to_string())
+            .with_method(Some(WifiMethod::Wpa))
+            .with_password(Some("pass:word".to_string()))
+            .with_hidden(true);
+        wifi.generate_image_file(None, &path).unwrap();
+        let decoded = Wifi::from_image(&path);
+
+        DppConfig::new(b"key".to_vec())
+            .generate_image_file(None, &path)
+            .unwrap();
+        let not_wifi = Wifi::from_image(&path);
+
+        image::GrayImage::from_pixel(64, 64, image::Luma([255]))
+            .save(&path)
+            .unwrap();
+        let blank = Wifi::from_image(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(decoded.unwrap(), wifi);
+        assert!(matches!(not_wifi, Err(GenerationError::NotWifiPayload)));
+        assert!(matches!(blank, Err(GenerationError::NoCodeFound)));
+    }
+}
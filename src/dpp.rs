@@ -0,0 +1,146 @@
+//! Wi-Fi Easy Connect (DPP) bootstrapping URIs, the successor of the `WIFI:` payload
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Display;
+
+use crate::Field;
+
+/// The `DPP:` URI a configurator scans to provision a device with Wi-Fi Easy Connect
+///
+/// Unlike [`Wifi`](crate::Wifi) it doesn't describe a network but the device that joins one,
+/// identified by its bootstrapping public key.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DppConfig {
+    public_key: Vec<u8>,
+    channels: Vec<(u8, u8)>,
+    mac: Option<[u8; 6]>,
+    info: Option<String>,
+}
+
+impl DppConfig {
+    /// `public_key` is the DER encoded `SubjectPublicKeyInfo` of the bootstrapping key
+    pub fn new(public_key: Vec<u8>) -> Self {
+        Self {
+            public_key,
+            channels: Vec::new(),
+            mac: None,
+            info: None,
+        }
+    }
+
+    /// The channels the device listens on, as pairs of global operating class and channel number
+    pub fn with_channels(mut self, channels: Vec<(u8, u8)>) -> Self {
+        self.channels = channels;
+        self
+    }
+
+    pub fn with_mac(mut self, mac: Option<[u8; 6]>) -> Self {
+        self.mac = mac;
+        self
+    }
+
+    /// Free form information about the device, e.g. a serial number
+    ///
+    /// The URI has no escaping, so anything but printable ASCII other than `;` is left out.
+    pub fn with_info(mut self, info: Option<String>) -> Self {
+        self.info = info;
+        self
+    }
+
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    pub fn channels(&self) -> &[(u8, u8)] {
+        &self.channels
+    }
+
+    pub fn mac(&self) -> Option<[u8; 6]> {
+        self.mac
+    }
+
+    pub fn info(&self) -> Option<&str> {
+        self.info.as_deref()
+    }
+
+    /// in the order the specification requires, the public key last
+    fn fields(&self) -> Vec<Field> {
+        let mut fields = Vec::with_capacity(4);
+
+        if !self.channels.is_empty() {
+            let channels = self
+                .channels
+                .iter()
+                .map(|(class, channel)| format!("{class}/{channel}"))
+                .collect::<Vec<_>>();
+            fields.push(Field {
+                name: "C".to_string(),
+                value: channels.join(","),
+            });
+        }
+
+        if let Some(mac) = &self.mac {
+            fields.push(Field::new_hex("M", mac));
+        }
+
+        if let Some(info) = &self.info {
+            fields.push(Field {
+                name: "I".to_string(),
+                value: info
+                    .chars()
+                    .filter(|&c| matches!(c, ' '..='~') && c != ';')
+                    .collect(),
+            });
+        }
+
+        fields.push(Field::new_base64("K", &self.public_key));
+
+        fields
+    }
+}
+
+impl Display for DppConfig {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("DPP:")?;
+        for field in self.fields() {
+            write!(f, "{field}")?;
+        }
+        f.write_str(";")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::Engine;
+
+    use super::*;
+
+    #[test]
+    fn public_key_round_trip() {
+        let key = (0..=255).collect::<Vec<u8>>();
+        let uri = DppConfig::new(key.clone()).to_string();
+
+        assert!(uri.starts_with("DPP:K:"), "{uri}");
+        let encoded = uri
+            .strip_prefix("DPP:K:")
+            .unwrap()
+            .strip_suffix(";;")
+            .unwrap();
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .unwrap();
+        assert_eq!(decoded, key);
+    }
+
+    #[test]
+    fn all_fields() {
+        let uri = DppConfig::new(b"key".to_vec())
+            .with_channels([(81, 1), (115, 36)].to_vec())
+            .with_mac(Some([0x00, 0x1a, 0x2b, 0x3c, 0x4d, 0x5e]))
+            .with_info(Some("SN;42\u{e9}".to_string()))
+            .to_string();
+        assert_eq!(uri, "DPP:C:81/1,115/36;M:001a2b3c4d5e;I:SN42;K:a2V5;;");
+    }
+}
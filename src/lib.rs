@@ -1,167 +1,152 @@
-use std::fmt::Debug;
-use std::fmt::Display;
-use std::path::Path;
+#![cfg_attr(not(feature = "std"), no_std)]
 
-use arqoii::types::QoiHeader;
-use base64::Engine;
-
-#[cfg(feature = "cli")]
-use clap::{builder::PossibleValue, ValueEnum};
-
-use image::ImageBuffer;
-use image::Luma;
-use qrcode::QrCode;
-use qrcode::render::Pixel;
-
-#[derive(Clone)]
-#[non_exhaustive]
-pub enum ImageFormat {
-    #[non_exhaustive]
-    ImageFormat(image::ImageFormat),
-    #[cfg(feature = "qoi")]
-    #[non_exhaustive]
-    Qoi,
-}
-
-impl Debug for ImageFormat {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ImageFormat::ImageFormat(format) => write!(f, "{format:?}"),
-            ImageFormat::Qoi => write!(f, "Qoi"),
-        }
-    }
-}
-
-impl Default for ImageFormat {
-    fn default() -> Self {
-        Self::ImageFormat(image::ImageFormat::Png)
-    }
-}
-
-#[cfg(feature = "cli")]
-impl ValueEnum for ImageFormat {
-    fn value_variants<'a>() -> &'a [Self] {
-        &[
-            Self::Qoi, Self::ImageFormat(image::ImageFormat::Png),
-            Self::ImageFormat(image::ImageFormat::Jpeg)
-        ]
-    }
-
-    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
-        let name = format!("{self:?}").to_lowercase();
-        Some(
-            PossibleValue::new(name),
-        )
-    }
-}
-
-impl ImageFormat {
-    pub fn png() -> Self {
-        Self::ImageFormat(image::ImageFormat::Png)
-    }
-
-    #[cfg(feature = "qoi")]
-    pub fn qoi() -> Self {
-        Self::Qoi
-    }
-}
-
-struct Image {
-    buffer: ImageBuffer<Luma<u8>, Vec<u8>>,
-}
-
-impl Image {
-    pub fn save(&self, format: ImageFormat, file_path: &Path) -> Result<(), GenerationError> {
-        match format {
-            ImageFormat::ImageFormat(format) => {
-                self.buffer.save_with_format(file_path, format)?;
-            }
-            ImageFormat::Qoi => {
-                let data = arqoii::QoiEncoder::new(
-                    QoiHeader::new(
-                        self.buffer.width(),
-                        self.buffer.height(),
-                        arqoii::types::QoiChannels::Rgb,
-                        arqoii::types::QoiColorSpace::SRgbWithLinearAlpha,
-                    ),
-                    self.buffer.pixels().map(|px| arqoii::Pixel {
-                        r: px.0[0],
-                        g: px.0[0],
-                        b: px.0[0],
-                        a: 255,
-                    }),
-                )
-                .collect::<Vec<_>>();
-                std::fs::write(file_path, data)?;
-            }
-        }
-        Ok(())
-    }
-    pub fn save_guess_format(&self, file_path: &Path) -> Result<(), GenerationError> {
-        if cfg!(feature = "qoi") && file_path.extension().is_some_and(|ext| ext == "qoi") {
-            self.save(ImageFormat::Qoi, file_path)
-        } else {
-            self.buffer.save(file_path)?;
-            Ok(())
-        }
-    }
-}
-
-
-#[derive(Debug, Clone, Copy)]
-struct Px(Luma<u8>);
-
-struct Canvas(Px, Image);
-
-impl Pixel for Px {
-    type Image = Image;
-
-    type Canvas = Canvas;
-
-    fn default_color(color: qrcode::Color) -> Self {
-        Self(Luma([color.select(0, 255)]))
-    }
-}
-
-impl qrcode::render::Canvas for Canvas {
-    type Pixel = Px;
+extern crate alloc;
 
-    type Image = <Px as Pixel>::Image;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use core::fmt::Display;
 
-    fn new(width: u32, height: u32, dark_pixel: Self::Pixel, light_pixel: Self::Pixel) -> Self {
-        Self(dark_pixel, Image { buffer: ImageBuffer::from_pixel(width, height, light_pixel.0) })
-    }
+use base64::Engine;
 
-    fn draw_dark_pixel(&mut self, x: u32, y: u32) {
-        self.1.buffer.put_pixel(x, y, self.0.0)
-    }
+#[cfg(feature = "std")]
+use qrcode::EcLevel;
+#[cfg(feature = "std")]
+use qrcode::QrCode;
 
-    fn into_image(self) -> Self::Image {
-        self.1
-    }
-}
+#[cfg(feature = "apple")]
+mod apple;
+#[cfg(feature = "image")]
+mod caption;
+#[cfg(feature = "image")]
+mod color;
+#[cfg(feature = "decode")]
+mod decode;
+mod dpp;
+mod parse;
+#[cfg(feature = "password-generator")]
+mod password;
+#[cfg(feature = "pdf")]
+mod pdf;
+#[cfg(feature = "image")]
+mod render;
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(feature = "svg")]
+mod svg;
+#[cfg(feature = "std")]
+mod terminal;
+mod url;
+mod validate;
+#[cfg(feature = "wasm")]
+mod wasm;
+
+#[cfg(feature = "image")]
+pub use color::parse_color;
+pub use dpp::DppConfig;
+#[cfg(feature = "password-generator")]
+pub use password::{generate_password, Charset};
+#[cfg(feature = "pdf")]
+pub use pdf::generate_index_pdf;
+#[cfg(feature = "image")]
+pub use render::{
+    generate_batch, generate_batch_ndjson, generate_grid, generate_spritesheet, FileNameSuffix,
+    ImageFormat, JpegOptions, PngCompression, PngFilter, PngOptions, RenderedImage, WebRender,
+};
+pub use validate::{validate_batch, ValidWifi, ValidationError};
 
 #[derive(Debug, thiserror::Error)]
 pub enum GenerationError {
+    #[cfg(feature = "std")]
     #[error("{0}")]
     QrError(#[from] qrcode::types::QrError),
+    #[cfg(feature = "image")]
     #[error("{0}")]
     ImageError(#[from] image::error::ImageError),
+    #[cfg(feature = "image")]
+    #[error("can't tell the image format of {} from its extension", .0.display())]
+    UnknownImageExtension(std::path::PathBuf),
+    #[cfg(feature = "std")]
     #[error("{0}")]
     Io(#[from] std::io::Error),
+    #[error("invalid color: {0}")]
+    InvalidColor(String),
+    #[error("invalid link: {0}")]
+    InvalidUrl(String),
+    #[error("invalid WIFI: payload: {0}")]
+    InvalidPayload(String),
+    #[error("{0}")]
+    ValidationError(#[from] ValidationError),
+    #[cfg(feature = "png-only")]
+    #[error("{0}")]
+    PngError(#[from] png::EncodingError),
+    #[cfg(feature = "webp")]
+    #[error("WebP encoding failed: {0:?}")]
+    WebPError(webp::WebPEncodingError),
+    #[cfg(feature = "zip")]
+    #[error("{0}")]
+    ZipError(#[from] zip::result::ZipError),
+    #[cfg(feature = "serde")]
+    #[error("{0}")]
+    JsonError(#[from] serde_json::Error),
+    #[cfg(feature = "password-generator")]
+    #[error("{0}")]
+    RandomError(#[from] getrandom::Error),
+    #[cfg(feature = "password-generator")]
+    #[error("a WPA passphrase has to be 8 to 63 characters long, not {0}")]
+    InvalidPasswordLength(usize),
+    #[cfg(feature = "apple")]
+    #[error("{0}")]
+    PlistError(#[from] plist::Error),
+    #[cfg(feature = "print")]
+    #[error("modules would only be {module_mm:.2}mm wide, too small to scan reliably")]
+    ModuleTooSmall { module_mm: f64 },
+    #[cfg(feature = "image")]
+    #[error("a {width}x{height} banner has no room for both the code and the text")]
+    BannerTooSmall { width: u32, height: u32 },
+    #[cfg(feature = "image-codecs")]
+    #[error("{0:?} images can't be animated")]
+    NotAnimated(ImageFormat),
+    #[cfg(feature = "image")]
+    #[error("{networks} codes don't fit into a grid of {cells} cells")]
+    GridTooSmall { networks: usize, cells: usize },
+    #[cfg(feature = "decode")]
+    #[error("no readable QR-Code found in the image")]
+    NoCodeFound,
+    #[cfg(feature = "decode")]
+    #[error("the QR-Code doesn't contain a `WIFI:` payload")]
+    NotWifiPayload,
+    #[cfg(feature = "verify")]
+    #[error("the rendered code doesn't decode to the payload: {0}")]
+    VerificationFailed(String),
 }
 
+/// With the `serde` feature only the network itself is (de)serialized, the rendering settings are not
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Wifi {
     ssid: String,
     kind: Option<WifiMethod>,
+    raw_auth_type: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
     hidden: bool,
+    #[cfg_attr(feature = "serde", serde(default = "serialize::default_true"))]
+    transition_disable: bool,
     eap_method: Option<EapMethod>,
     phase2: Option<Phase2>,
     anonymous_identity: Option<String>,
     identity: Option<String>,
     password: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default, with = "serialize::psk"))]
+    psk: Option<[u8; 32]>,
+    #[cfg_attr(feature = "serde", serde(default, with = "serialize::public_key"))]
     public_key: Option<Vec<u8>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    raw_fields: Vec<(String, String)>,
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    render_options: RenderOptions,
 }
 
 impl Wifi {
@@ -169,13 +154,19 @@ impl Wifi {
         Self {
             ssid,
             kind: None,
+            raw_auth_type: None,
             hidden: false,
+            transition_disable: true,
             eap_method: None,
             phase2: None,
             anonymous_identity: None,
             identity: None,
             password: None,
+            psk: None,
             public_key: None,
+            raw_fields: Vec::new(),
+            #[cfg(feature = "std")]
+            render_options: RenderOptions::default(),
         }
     }
 
@@ -184,11 +175,31 @@ impl Wifi {
         self
     }
 
+    /// Emit exactly this `T` value instead of the one derived from the [`WifiMethod`].
+    ///
+    /// This bypasses the validated method mapping and is only meant for debugging
+    /// scanner compatibility, e.g. trying `WPA2` literally.
+    /// The value is still escaped.
+    pub fn with_raw_auth_type(mut self, auth_type: Option<String>) -> Self {
+        self.raw_auth_type = auth_type;
+        self
+    }
+
     pub fn with_hidden(mut self, hidden: bool) -> Self {
         self.hidden = hidden;
         self
     }
 
+    /// Whether a [`WifiMethod::Wpa3`] network disables the WPA2 transition mode with the `R` field,
+    /// which protects against downgrade attacks, enabled by default.
+    ///
+    /// Disable it while devices that only support WPA2 still have to join the network.
+    /// Ignored for other methods.
+    pub fn with_transition_disable(mut self, transition_disable: bool) -> Self {
+        self.transition_disable = transition_disable;
+        self
+    }
+
     pub fn with_eap_method(mut self, eap: Option<EapMethod>) -> Self {
         self.eap_method = eap;
         self
@@ -209,47 +220,194 @@ impl Wifi {
         self
     }
 
+    /// The password is always a passphrase (or WEP key), never a raw PSK, see [`Wifi::with_raw_psk`]
+    ///
+    /// It's only quoted if it is 64 hex digits, so readers don't take it for a raw PSK.
+    /// [`Wifi::validate`] rejects a network with both a password and a raw PSK.
     pub fn with_password(mut self, pw: Option<String>) -> Self {
         self.password = pw;
         self
     }
 
+    /// Use a pre-computed 256-bit PSK instead of a passphrase
+    ///
+    /// The key is emitted as 64 hex digits without quotes. The ZXing `WIFI:` format, which phones
+    /// follow, reads an unquoted 64 digit hex `P` as a raw PSK and a quoted one as a passphrase,
+    /// so quoting the key would turn it into a 64 character passphrase.
+    /// [`Wifi::validate`] rejects a network with both a raw PSK and a password.
+    pub fn with_raw_psk(mut self, psk: Option<[u8; 32]>) -> Self {
+        self.psk = psk;
+        self
+    }
+
+    /// Shorthand for [`Wifi::with_raw_psk`] with a key
+    pub fn with_psk_hex(self, psk: [u8; 32]) -> Self {
+        self.with_raw_psk(Some(psk))
+    }
+
     pub fn with_public_key(mut self, pk: Option<Vec<u8>>) -> Self {
         self.public_key = pk;
         self
     }
 
-    pub fn generate_image_file(
-        &self,
-        format: Option<ImageFormat>,
-        file_path: &Path,
-    ) -> Result<(), GenerationError> {
-        let code = QrCode::new(self.to_string())?;
+    /// Append a field the crate doesn't model, e.g. a vendor specific `O:` tag
+    ///
+    /// Name and value are escaped like any other field, raw fields are written after all others.
+    pub fn with_raw_field(mut self, name: String, value: String) -> Self {
+        self.raw_fields.push((name, value));
+        self
+    }
+
+    pub fn ssid(&self) -> &str {
+        &self.ssid
+    }
+
+    pub fn method(&self) -> Option<&WifiMethod> {
+        self.kind.as_ref()
+    }
+
+    /// The `T` value set with [`Wifi::with_raw_auth_type`]
+    pub fn raw_auth_type(&self) -> Option<&str> {
+        self.raw_auth_type.as_deref()
+    }
 
-        let image = code.render::<Px>().build();
+    pub fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    pub fn transition_disable(&self) -> bool {
+        self.transition_disable
+    }
+
+    pub fn eap_method(&self) -> Option<&EapMethod> {
+        self.eap_method.as_ref()
+    }
+
+    pub fn phase2(&self) -> Option<&Phase2> {
+        self.phase2.as_ref()
+    }
+
+    pub fn anonymous_identity(&self) -> Option<&str> {
+        self.anonymous_identity.as_deref()
+    }
 
-        match format {
-            Some(format) => image.save(format, file_path)?,
-            None => image.save_guess_format(file_path)?,
+    pub fn identity(&self) -> Option<&str> {
+        self.identity.as_deref()
+    }
+
+    /// The passphrase, `None` if a raw PSK is used instead
+    pub fn password(&self) -> Option<&str> {
+        self.password.as_deref()
+    }
+
+    /// The raw PSK set with [`Wifi::with_raw_psk`]
+    pub fn psk(&self) -> Option<&[u8; 32]> {
+        self.psk.as_ref()
+    }
+
+    pub fn public_key(&self) -> Option<&[u8]> {
+        self.public_key.as_deref()
+    }
+
+    /// The fields added with [`Wifi::with_raw_field`], as name and value
+    pub fn raw_fields(&self) -> &[(String, String)] {
+        &self.raw_fields
+    }
+
+    /// Whether joining this network needs a password, i.e. any method other than open or OWE
+    pub fn requires_password(&self) -> bool {
+        match self.kind {
+            None | Some(WifiMethod::NoPass) | Some(WifiMethod::Owe) => false,
+            Some(WifiMethod::Wep)
+            | Some(WifiMethod::Wpa)
+            | Some(WifiMethod::Wpa2)
+            | Some(WifiMethod::Wpa2Enterprise)
+            | Some(WifiMethod::Wpa3) => true,
         }
+    }
+
+    /// Whether this network uses EAP (WPA2-Enterprise)
+    pub fn is_enterprise(&self) -> bool {
+        matches!(self.kind, Some(WifiMethod::Wpa2Enterprise))
+    }
+
+    /// The `WIFI:...;` string encoded in the QR-Code
+    ///
+    /// Same as the [`Display`] output, prefer this where the payload is meant.
+    pub fn payload(&self) -> String {
+        self.to_string()
+    }
+
+    /// The payload as returned by [`Wifi::payload`], but with the password and public key
+    /// replaced by `****`, so it can be logged safely
+    ///
+    /// Same as [`Wifi::redacted_payload`] without masking the identities.
+    pub fn to_redacted_string(&self) -> String {
+        self.redacted_payload(false)
+    }
 
-        Ok(())
+    /// The payload as returned by [`Wifi::payload`], but with the password and public key
+    /// replaced by `****`, and with `mask_identities` the EAP identity and anonymous identity too
+    pub fn redacted_payload(&self, mask_identities: bool) -> String {
+        let content: String = self
+            .fields()
+            .into_iter()
+            .map(|mut field| {
+                let sensitive = match field.name.as_str() {
+                    "P" | "K" => true,
+                    "I" | "A" => mask_identities,
+                    _ => false,
+                };
+                if sensitive {
+                    field.value = "****".to_string();
+                }
+                field.to_string()
+            })
+            .collect();
+        format!("WIFI:{content};")
+    }
+
+    /// The length in bytes of the escaped payload returned by [`Wifi::payload`],
+    /// without assembling the payload
+    pub fn payload_len(&self) -> usize {
+        let fields: usize = self
+            .fields()
+            .iter()
+            .map(|field| field.name.len() + field.value.len() + ":;".len())
+            .sum();
+        "WIFI:".len() + fields + ";".len()
+    }
+
+    /// A stable 64-bit FNV-1a hash of the payload as 16 hex digits,
+    /// e.g. to tell generated files apart
+    pub fn fingerprint(&self) -> String {
+        let hash = self
+            .payload()
+            .bytes()
+            .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+            });
+        format!("{hash:016x}")
+    }
+
+    /// whether the `R` field gets emitted
+    fn disables_transition(&self) -> bool {
+        self.transition_disable && matches!(self.kind, Some(WifiMethod::Wpa3))
     }
 
     fn expected_field_count(&self) -> usize {
-        self.kind.as_ref().map_or(0, |method|if let WifiMethod::Wpa3 = method {
-            2
-        } else {
-            1
-        })
+        self.kind.is_some() as usize
+         + self.disables_transition() as usize
+         + (self.kind.is_none() && self.raw_auth_type.is_some()) as usize
          + 1 // ssid is required
             + self.hidden as usize
             + self.eap_method.is_some() as usize
             + self.phase2.is_some() as usize
             + self.anonymous_identity.is_some() as usize
             + self.identity.is_some() as usize
-            + self.password.is_some() as usize
+            + (self.password.is_some() || self.psk.is_some()) as usize
             + self.public_key.is_some() as usize
+            + self.raw_fields.len()
     }
 
     fn fields(&self) -> Vec<Field> {
@@ -261,6 +419,21 @@ impl Wifi {
             kind.add_fields(&mut fields);
         }
 
+        if self.disables_transition() {
+            // https://superuser.com/a/1752085
+            // https://www.wi-fi.org/file/wpa3tm-specification
+            // https://www.wi-fi.org/system/files/WPA3%20Specification%20v3.1.pdf
+            fields.push(Field::new_string("R", "1"))
+        }
+
+        if let Some(auth_type) = &self.raw_auth_type {
+            let auth_type = Field::new_string("T", auth_type);
+            match fields.iter_mut().find(|field| field.name == "T") {
+                Some(field) => *field = auth_type,
+                None => fields.insert(0, auth_type),
+            }
+        }
+
         fields.push(Field::new_string("S", &self.ssid));
 
         if self.hidden {
@@ -287,18 +460,402 @@ impl Wifi {
             fields.push(Field::new_string("P", password));
         }
 
+        if let Some(psk) = &self.psk {
+            fields.push(Field::new_hex("P", psk));
+        }
+
         if let Some(pk) = &self.public_key {
             fields.push(Field::new_base64("K", pk));
         }
 
+        for (name, value) in &self.raw_fields {
+            fields.push(Field::new_string(Field::escape(name), value));
+        }
+
         fields
     }
 }
 
-impl ToString for Wifi {
-    fn to_string(&self) -> String {
-        let content: String = self.fields().into_iter().map(|f| f.to_string()).collect();
-        format!("WIFI:{content};")
+#[cfg(feature = "std")]
+impl Wifi {
+    /// Error correction level of the rendered code, [`EcLevel::M`] if not set
+    /// ([`EcLevel::H`] if the code has a logo, [`EcLevel::Q`] with a caption, rounded finders
+    /// or a quiet zone pattern, see [`RenderReport::ec_level_raised`]).
+    ///
+    /// Higher levels survive more damage (or a logo over the center) at the cost of a larger code.
+    pub fn with_ec_level(mut self, ec_level: Option<EcLevel>) -> Self {
+        self.render_options.ec_level = ec_level;
+        self
+    }
+
+    /// RGBA colors of the dark modules and of the light modules and quiet zone
+    pub fn with_colors(mut self, dark: [u8; 4], light: [u8; 4]) -> Self {
+        self.render_options.dark = dark;
+        self.render_options.light = light;
+        self
+    }
+
+    /// Draw the dark modules in the light color and the light modules and quiet zone
+    /// in the dark color, see [`Wifi::with_colors`]
+    ///
+    /// Not every scanner reads inverted codes.
+    pub fn with_inverted(mut self, inverted: bool) -> Self {
+        self.render_options.inverted = inverted;
+        self
+    }
+
+    /// Size of a module in pixels (or SVG user units), 8 if not set
+    pub fn with_module_size(mut self, module_size: u32) -> Self {
+        self.render_options.module_size = Some(module_size.max(1));
+        self
+    }
+
+    /// Smallest size of the whole image including the quiet zone, see [`RenderOptions::min_dimensions`]
+    pub fn with_min_dimensions(mut self, width: u32, height: u32) -> Self {
+        self.render_options.min_dimensions = Some((width, height));
+        self
+    }
+
+    /// Surround the code with the light quiet zone scanners need to find it, on by default
+    pub fn with_quiet_zone(mut self, quiet_zone: bool) -> Self {
+        self.render_options.quiet_zone = quiet_zone;
+        self
+    }
+
+    /// Width of the quiet zone in modules, 4 if not set
+    pub fn with_border(mut self, border: Option<u32>) -> Self {
+        self.render_options.border = border;
+        self
+    }
+
+    pub fn with_render_options(mut self, render_options: RenderOptions) -> Self {
+        self.render_options = render_options;
+        self
+    }
+
+    pub fn render_options(&self) -> &RenderOptions {
+        &self.render_options
+    }
+
+    /// The payload as returned by [`Wifi::payload`], but fails with [`GenerationError::QrError`]
+    /// if it doesn't fit into a QR-Code, without rendering anything
+    ///
+    /// The capacity is checked at the same error correction level that is used for rendering.
+    pub fn try_render_payload(&self) -> Result<String, GenerationError> {
+        let payload = self.payload();
+        qrcode::bits::encode_auto(payload.as_bytes(), self.ec_level().unwrap_or(EcLevel::M))?;
+        Ok(payload)
+    }
+
+    /// The smallest QR version the payload fits into at the configured error correction level,
+    /// without rendering the code
+    pub fn estimate_qr_version(&self) -> Result<qrcode::Version, GenerationError> {
+        let ec_level = self.ec_level().unwrap_or(EcLevel::M);
+        Ok(qrcode::bits::encode_auto(self.payload().as_bytes(), ec_level)?.version())
+    }
+
+    /// Whether the payload fits into a code of `version` at `ec_level`,
+    /// e.g. to stay below the density a scanner can handle
+    pub fn payload_fits(&self, version: qrcode::Version, ec_level: EcLevel) -> bool {
+        let mut bits = qrcode::bits::Bits::new(version);
+        bits.push_optimal_data(self.payload().as_bytes()).is_ok()
+            && bits.push_terminator(ec_level).is_ok()
+    }
+
+    /// Report the QR version and error correction level that get chosen for this network
+    pub fn render_report(&self) -> Result<RenderReport, GenerationError> {
+        let code = self.qr_code()?;
+
+        Ok(RenderReport {
+            version: code.version(),
+            ec_level: code.error_correction_level(),
+            ec_level_raised: self.render_options.ec_level.is_none()
+                && self.overlay_ec_level().is_some(),
+            modules: code.width(),
+            payload_bytes: self.payload().len(),
+        })
+    }
+
+    /// The modules of the QR-Code row by row, `true` for dark ones,
+    /// for rendering the code yourself
+    ///
+    /// There is no quiet zone around the modules.
+    pub fn generate_matrix(&self) -> Result<Vec<Vec<bool>>, GenerationError> {
+        let code = self.qr_code()?;
+        let width = code.width();
+        Ok(code
+            .into_colors()
+            .chunks(width)
+            .map(|row| row.iter().map(|&color| color == qrcode::Color::Dark).collect())
+            .collect())
+    }
+
+    /// A renderer for the `colors` of a code `width` modules wide,
+    /// with the quiet zone and module size of the render options applied
+    #[cfg(any(feature = "image", feature = "svg"))]
+    fn renderer<'a, P: qrcode::render::Pixel>(
+        &self,
+        colors: &'a [qrcode::Color],
+        width: usize,
+    ) -> qrcode::render::Renderer<'a, P> {
+        let quiet_zone = self.render_options.quiet_zone_width();
+        let mut renderer = qrcode::render::Renderer::new(colors, width, quiet_zone);
+        let full_width = width as u32 + 2 * quiet_zone;
+        if let Some(module_size) = self.render_options.module_size(full_width) {
+            renderer.module_dimensions(module_size, module_size);
+        }
+        renderer
+    }
+
+    /// the configured level, or the one needed for the overlays if none is set
+    fn ec_level(&self) -> Option<EcLevel> {
+        self.render_options.ec_level.or(self.overlay_ec_level())
+    }
+
+    /// [`EcLevel::H`] when a logo covers part of the code,
+    /// [`EcLevel::Q`] for the other overlays that change how the code looks
+    fn overlay_ec_level(&self) -> Option<EcLevel> {
+        #[cfg(feature = "image")]
+        {
+            let options = &self.render_options;
+            if options.logo.is_some() {
+                return Some(EcLevel::H);
+            }
+            if options.caption.is_some()
+                || options.rounded_finders
+                || options.quiet_zone_pattern.is_some()
+            {
+                return Some(EcLevel::Q);
+            }
+        }
+        None
+    }
+
+    fn qr_code(&self) -> Result<QrCode, GenerationError> {
+        Ok(match self.ec_level() {
+            Some(ec_level) => QrCode::with_error_correction_level(self.payload(), ec_level)?,
+            None => QrCode::new(self.payload())?,
+        })
+    }
+}
+
+/// How the QR-Code of a network is drawn, shared by all output formats
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderOptions {
+    /// see [`Wifi::with_ec_level`]
+    pub ec_level: Option<EcLevel>,
+    /// RGBA color of the dark modules
+    pub dark: [u8; 4],
+    /// RGBA color of the light modules and the quiet zone
+    pub light: [u8; 4],
+    /// Make the light modules and the quiet zone fully transparent, regardless of `light`.
+    ///
+    /// Black on white images stay grayscale, but get an alpha channel.
+    /// JPEG has no alpha channel, so it gets a white background instead.
+    pub transparent_background: bool,
+    /// see [`Wifi::with_module_size`]
+    pub module_size: Option<u32>,
+    /// The modules are made just large enough for the image to be at least this large.
+    ///
+    /// Modules stay square, so the larger of both dimensions decides.
+    /// If `module_size` is set as well, whichever results in the larger modules wins.
+    pub min_dimensions: Option<(u32, u32)>,
+    /// see [`Wifi::with_quiet_zone`]
+    pub quiet_zone: bool,
+    /// see [`Wifi::with_border`]
+    pub border: Option<u32>,
+    /// Swap `dark` and `light`, i.e. light modules on a dark background
+    pub inverted: bool,
+    /// see [`Wifi::with_mirror`]
+    #[cfg(feature = "image")]
+    pub mirror: bool,
+    /// see [`Wifi::with_scan_guide`]
+    #[cfg(feature = "image")]
+    pub scan_guide: bool,
+    /// see [`Wifi::with_rounded_finders`]
+    #[cfg(feature = "image")]
+    pub rounded_finders: bool,
+    /// The logo and the share of the code width it may cover, see [`Wifi::with_logo`]
+    #[cfg(feature = "image")]
+    pub logo: Option<(image::DynamicImage, f32)>,
+    /// see [`Wifi::with_logo_quiet_zone`]
+    #[cfg(feature = "image")]
+    pub logo_quiet_zone: u32,
+    /// see [`Wifi::with_quiet_zone_pattern`]
+    #[cfg(feature = "image")]
+    pub quiet_zone_pattern: Option<image::DynamicImage>,
+    /// see [`Wifi::with_caption`]
+    #[cfg(feature = "image")]
+    pub caption: Option<String>,
+    /// see [`Wifi::with_png_options`]
+    #[cfg(feature = "image")]
+    pub png_options: Option<PngOptions>,
+    /// see [`Wifi::with_jpeg_options`]
+    #[cfg(feature = "image")]
+    pub jpeg_options: Option<JpegOptions>,
+    /// The width in mm and the resolution in DPI, see [`Wifi::for_print`]
+    #[cfg(feature = "print")]
+    pub print_size: Option<(f64, u32)>,
+    /// see [`Wifi::with_file_name_suffix`]
+    #[cfg(feature = "image")]
+    pub file_name_suffix: FileNameSuffix,
+    /// see [`Wifi::with_embed_payload_metadata`]
+    #[cfg(feature = "svg")]
+    pub embed_payload_metadata: bool,
+    /// see [`Wifi::with_redacted_payload_metadata`]
+    #[cfg(feature = "svg")]
+    pub redact_payload_metadata: bool,
+}
+
+#[cfg(feature = "std")]
+impl RenderOptions {
+    /// Width of the quiet zone on each side, in modules
+    fn quiet_zone_width(&self) -> u32 {
+        if self.quiet_zone {
+            self.border.unwrap_or(4)
+        } else {
+            0
+        }
+    }
+
+    /// The colors of the dark and of the light modules, swapped if `inverted`
+    #[cfg(any(feature = "image", feature = "svg"))]
+    fn colors(&self) -> ([u8; 4], [u8; 4]) {
+        if self.inverted {
+            (self.light, self.dark)
+        } else {
+            (self.dark, self.light)
+        }
+    }
+
+    /// The module size for a code `width` modules wide (including the quiet zone),
+    /// `None` to keep the renderer's default
+    #[cfg(any(feature = "image", feature = "svg"))]
+    fn module_size(&self, width: u32) -> Option<u32> {
+        match self.min_dimensions {
+            Some((min_width, min_height)) => Some(
+                self.module_size
+                    .unwrap_or(1)
+                    .max(min_width.max(min_height).div_ceil(width)),
+            ),
+            None => self.module_size,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            ec_level: None,
+            dark: [0, 0, 0, 255],
+            light: [255, 255, 255, 255],
+            transparent_background: false,
+            module_size: None,
+            min_dimensions: None,
+            quiet_zone: true,
+            border: None,
+            inverted: false,
+            #[cfg(feature = "image")]
+            mirror: false,
+            #[cfg(feature = "image")]
+            scan_guide: false,
+            #[cfg(feature = "image")]
+            rounded_finders: false,
+            #[cfg(feature = "image")]
+            logo: None,
+            #[cfg(feature = "image")]
+            logo_quiet_zone: 0,
+            #[cfg(feature = "image")]
+            quiet_zone_pattern: None,
+            #[cfg(feature = "image")]
+            caption: None,
+            #[cfg(feature = "image")]
+            png_options: None,
+            #[cfg(feature = "image")]
+            jpeg_options: None,
+            #[cfg(feature = "print")]
+            print_size: None,
+            #[cfg(feature = "image")]
+            file_name_suffix: FileNameSuffix::None,
+            #[cfg(feature = "svg")]
+            embed_payload_metadata: false,
+            #[cfg(feature = "svg")]
+            redact_payload_metadata: true,
+        }
+    }
+}
+
+/// Details about the QR-Code generated for a network, see [`Wifi::render_report`]
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+pub struct RenderReport {
+    pub version: qrcode::Version,
+    pub ec_level: qrcode::EcLevel,
+    /// whether no level was set and the overlays raised it above the default
+    pub ec_level_raised: bool,
+    /// width and height of the code in modules, without the quiet zone
+    pub modules: usize,
+    pub payload_bytes: usize,
+}
+
+#[cfg(feature = "std")]
+impl Display for RenderReport {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.version {
+            qrcode::Version::Normal(version) => write!(f, "QR version {version}")?,
+            qrcode::Version::Micro(version) => write!(f, "Micro QR version M{version}")?,
+        }
+        write!(f, ", error correction {:?}", self.ec_level)?;
+        if self.ec_level_raised {
+            write!(f, " (raised for the overlays)")?;
+        }
+        write!(
+            f,
+            ", {modules}x{modules} modules, {} payload bytes",
+            self.payload_bytes,
+            modules = self.modules,
+        )
+    }
+}
+
+impl Wifi {
+    /// Everything that ends up in the payload, i.e. all fields but the render options
+    fn payload_key(&self) -> impl PartialEq + core::hash::Hash + '_ {
+        (
+            (&self.ssid, &self.kind, &self.raw_auth_type),
+            (self.hidden, self.disables_transition()),
+            (&self.eap_method, &self.phase2),
+            (&self.anonymous_identity, &self.identity),
+            (&self.password, &self.psk, &self.public_key),
+            &self.raw_fields,
+        )
+    }
+}
+
+/// Networks are equal if they have the same payload, the render options are ignored
+impl PartialEq for Wifi {
+    fn eq(&self, other: &Self) -> bool {
+        self.payload_key() == other.payload_key()
+    }
+}
+
+impl Eq for Wifi {}
+
+impl core::hash::Hash for Wifi {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.payload_key().hash(state);
+    }
+}
+
+impl Display for Wifi {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("WIFI:")?;
+        for field in self.fields() {
+            write!(f, "{field}")?;
+        }
+        f.write_str(";")
     }
 }
 
@@ -325,18 +882,12 @@ impl Field {
     fn new_hex(name: impl AsRef<str>, value: impl AsRef<[u8]>) -> Self {
         Self {
             name: name.as_ref().to_string(),
-            value: value.as_ref().iter().map(|b| format!("{b:x}")).collect(),
+            value: value.as_ref().iter().map(|b| format!("{b:02x}")).collect(),
         }
     }
 
     fn escape_field_value(value: &str) -> String {
-        // escape \ first so we don't escape the escape sequences
-        let value = value
-            .replace('\\', "\\\\")
-            .replace(';', "\\;")
-            .replace(',', "\\,")
-            .replace('"', "\\\"")
-            .replace(':', "\\:");
+        let value = Self::escape(value);
 
         if Self::could_be_ascii_hex(&value) {
             format!("\"{value}\"")
@@ -345,59 +896,84 @@ impl Field {
         }
     }
 
+    /// Backslash-escape the characters with a special meaning in the payload
+    ///
+    /// Control characters have no escape, [`Wifi::validate`] rejects them instead.
+    fn escape(value: &str) -> String {
+        // escape \ first so we don't escape the escape sequences
+        value
+            .replace('\\', "\\\\")
+            .replace(';', "\\;")
+            .replace(',', "\\,")
+            .replace('"', "\\\"")
+            .replace(':', "\\:")
+    }
+
+    /// Whether a reader would take the value for a raw 256-bit PSK,
+    /// shorter hex-looking values like `deadbeef` are still read as text
+    ///
+    /// Following the ZXing format, quotes mark such a value as a passphrase,
+    /// so passwords get quoted, but never a PSK set with [`Wifi::with_raw_psk`].
     fn could_be_ascii_hex(value: &str) -> bool {
-        for c in value.chars() {
-            if !"0123456789abcdef".contains(c) {
-                return false;
-            }
-        }
-        true
+        value.len() == 64 && value.bytes().all(|b| b.is_ascii_hexdigit())
     }
 }
 
 impl Display for Field {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}:{};", self.name, self.value)
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[non_exhaustive]
 pub enum WifiMethod {
     NoPass,
     Wep,
     /// WPA is also used for WPA2 and WPA3
     Wpa,
+    /// WPA2-Personal, the payload is the same as for [`WifiMethod::Wpa`],
+    /// the QR format has no separate value for it
+    #[cfg_attr(feature = "cli", value(alias = "wpa2-personal"))]
+    #[cfg_attr(feature = "serde", serde(alias = "wpa2-personal"))]
+    Wpa2,
     Wpa2Enterprise,
-    /// Same as WPA, but for devices that support it includes a flag for WPA2/WPA3 transition mode disabled, to prevent downgrade attacks
+    /// Same as WPA, but for devices that support it includes a flag for WPA2/WPA3 transition mode disabled, to prevent downgrade attacks,
+    /// see [`Wifi::with_transition_disable`]
     Wpa3,
+    /// Enhanced Open (OWE), encrypted but without a password
+    ///
+    /// There is no dedicated `T` value for OWE, so this uses the same `nopass` as [`WifiMethod::NoPass`].
+    /// Android (10+) tries both an open and an Enhanced Open network for `nopass`,
+    /// older devices will only find the network if it runs in OWE transition mode.
+    Owe,
 }
 
 impl WifiMethod {
     pub fn add_fields(&self, fields: &mut Vec<Field>) {
         let kind = match self {
-            WifiMethod::NoPass => "nopass",
+            WifiMethod::NoPass
+            // Android's WifiQrCode treats nopass as "open network or OWE"
+            | WifiMethod::Owe => "nopass",
             WifiMethod::Wep => "WEP",
             WifiMethod::Wpa
+            | WifiMethod::Wpa2
             // https://superuser.com/a/1752085
             | WifiMethod::Wpa3 => "WPA",
             WifiMethod::Wpa2Enterprise => "WPA2-EAP",
         };
 
         fields.push(Field::new_string("T", kind));
-
-        if let WifiMethod::Wpa3 = self {
-            // https://superuser.com/a/1752085
-            // https://www.wi-fi.org/file/wpa3tm-specification
-            // https://www.wi-fi.org/system/files/WPA3%20Specification%20v3.1.pdf
-            fields.push(Field::new_hex("R", [1]))
-        }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[non_exhaustive]
 pub enum EapMethod {
     Peap,
@@ -424,8 +1000,10 @@ impl EapMethod {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[non_exhaustive]
 pub enum Phase2 {
     MsChap,
@@ -451,3 +1029,231 @@ impl Phase2 {
         fields.push(Field::new_string("PH2", ph2_name));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacted_string() {
+        let wifi = Wifi::new("Cafe".to_string())
+            .with_method(Some(WifiMethod::Wpa2Enterprise))
+            .with_eap_method(Some(EapMethod::Tls))
+            .with_identity(Some("guest".to_string()))
+            .with_password(Some("hunter2;x".to_string()))
+            .with_public_key(Some(b"key".to_vec()));
+
+        assert_eq!(
+            wifi.to_redacted_string(),
+            "WIFI:T:WPA2-EAP;S:Cafe;E:TLS;I:guest;P:****;K:****;;"
+        );
+        assert_eq!(
+            wifi.payload(),
+            r"WIFI:T:WPA2-EAP;S:Cafe;E:TLS;I:guest;P:hunter2\;x;K:a2V5;;"
+        );
+    }
+
+    #[test]
+    fn owe_has_no_password() {
+        let wifi = Wifi::new("Enhanced Open".to_string()).with_method(Some(WifiMethod::Owe));
+        assert_eq!(wifi.payload(), "WIFI:T:nopass;S:Enhanced Open;;");
+        assert!(!wifi.payload().contains("P:"));
+        assert!(!wifi.requires_password());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn render_report() {
+        let wifi = Wifi::new("Test".to_string())
+            .with_method(Some(WifiMethod::Wpa))
+            .with_password(Some("password".to_string()));
+        let report = wifi.render_report().unwrap();
+
+        assert_eq!(report.payload_bytes, "WIFI:T:WPA;S:Test;P:password;;".len());
+        assert_eq!(report.version, qrcode::Version::Normal(3));
+        assert_eq!(report.ec_level, EcLevel::M);
+        assert!(!report.ec_level_raised);
+        assert_eq!(report.modules, 29);
+        assert_eq!(
+            report.to_string(),
+            "QR version 3, error correction M, 29x29 modules, 30 payload bytes"
+        );
+    }
+
+    #[test]
+    fn classification() {
+        let classify = |kind| {
+            let wifi = Wifi::new("Test".to_string()).with_method(kind);
+            (wifi.requires_password(), wifi.is_enterprise())
+        };
+
+        assert_eq!(classify(None), (false, false));
+        assert_eq!(classify(Some(WifiMethod::NoPass)), (false, false));
+        assert_eq!(classify(Some(WifiMethod::Owe)), (false, false));
+        assert_eq!(classify(Some(WifiMethod::Wep)), (true, false));
+        assert_eq!(classify(Some(WifiMethod::Wpa)), (true, false));
+        assert_eq!(classify(Some(WifiMethod::Wpa2)), (true, false));
+        assert_eq!(classify(Some(WifiMethod::Wpa3)), (true, false));
+        assert_eq!(classify(Some(WifiMethod::Wpa2Enterprise)), (true, true));
+    }
+
+    #[test]
+    fn psk_hex() {
+        let mut psk = [0xab; 32];
+        psk[0] = 0x01;
+        let hex = format!("01{}", "ab".repeat(31));
+
+        let wifi = Wifi::new("Test".to_string())
+            .with_method(Some(WifiMethod::Wpa))
+            .with_psk_hex(psk);
+        assert_eq!(wifi.payload(), format!("WIFI:T:WPA;S:Test;P:{hex};;"));
+        assert_eq!(wifi.password(), None);
+
+        // the same digits as a passphrase get quoted, so they aren't read as a PSK
+        let wifi = wifi.with_raw_psk(None).with_password(Some(hex.clone()));
+        assert_eq!(wifi.payload(), format!("WIFI:T:WPA;S:Test;P:\"{hex}\";;"));
+        assert_eq!(wifi.psk(), None);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn try_render_payload() {
+        let wifi = Wifi::new("Test".to_string()).with_method(Some(WifiMethod::NoPass));
+        assert_eq!(wifi.try_render_payload().unwrap(), wifi.payload());
+
+        let wifi = wifi.with_raw_field("X".to_string(), "x".repeat(3000));
+        assert!(matches!(
+            wifi.try_render_payload(),
+            Err(GenerationError::QrError(qrcode::types::QrError::DataTooLong))
+        ));
+    }
+
+    #[test]
+    fn payload_len() {
+        let networks = [
+            Wifi::new("Test".to_string()),
+            Wifi::new("Café; \"Bar\"".to_string())
+                .with_method(Some(WifiMethod::Wpa3))
+                .with_password(Some(r"back\slash:colon,comma".to_string()))
+                .with_hidden(true),
+            Wifi::new("Test".to_string())
+                .with_method(Some(WifiMethod::Wpa))
+                .with_password(Some("ab".repeat(32))),
+            Wifi::new("Test".to_string())
+                .with_method(Some(WifiMethod::Wpa2Enterprise))
+                .with_eap_method(Some(EapMethod::Peap))
+                .with_phase2(Some(Phase2::MsChapV2))
+                .with_anonymous_identity(Some("anonymous".to_string()))
+                .with_identity(Some("user".to_string()))
+                .with_public_key(Some([1, 2, 3, 4].to_vec()))
+                .with_raw_field("O;".to_string(), "vendor".to_string()),
+        ];
+        for wifi in networks {
+            assert_eq!(wifi.payload_len(), wifi.payload().len(), "{wifi}");
+        }
+    }
+
+    #[test]
+    fn hex_field() {
+        let field = Field::new_hex("X", [0x00, 0x01, 0x0f, 0xff]);
+        assert_eq!(field.value, "00010fff");
+    }
+
+    #[test]
+    fn transition_disable_flag() {
+        let wifi = Wifi::new("Test".to_string())
+            .with_method(Some(WifiMethod::Wpa3))
+            .with_password(Some("password".to_string()));
+        assert_eq!(wifi.payload(), "WIFI:T:WPA;R:1;S:Test;P:password;;");
+    }
+
+    #[test]
+    fn quoting() {
+        // short hex-looking passwords are plain passphrases
+        assert_eq!(Field::escape_field_value("deadbeef"), "deadbeef");
+        assert_eq!(Field::escape_field_value("cafe"), "cafe");
+
+        let hex = "0123456789abcdefABCDEF".repeat(3)[..64].to_string();
+        assert_eq!(Field::escape_field_value(&hex), format!("\"{hex}\""));
+        // one digit off isn't a PSK either
+        assert_eq!(Field::escape_field_value(&hex[..63]), hex[..63]);
+    }
+
+    #[test]
+    fn transition_mode() {
+        let wifi = Wifi::new("Test".to_string())
+            .with_method(Some(WifiMethod::Wpa3))
+            .with_password(Some("password".to_string()));
+        assert!(wifi.transition_disable());
+        assert!(wifi.payload().contains("R:1;"));
+
+        let wifi = wifi.with_transition_disable(false);
+        assert_eq!(wifi.payload(), "WIFI:T:WPA;S:Test;P:password;;");
+
+        // only WPA3 networks get the flag
+        let wifi = wifi
+            .with_transition_disable(true)
+            .with_method(Some(WifiMethod::Wpa));
+        assert!(!wifi.payload().contains("R:"));
+    }
+
+    #[test]
+    fn raw_auth_type() {
+        let wifi = Wifi::new("Test".to_string())
+            .with_method(Some(WifiMethod::Wpa))
+            .with_password(Some("password".to_string()))
+            .with_raw_auth_type(Some("WPA2".to_string()));
+        assert_eq!(wifi.payload(), "WIFI:T:WPA2;S:Test;P:password;;");
+
+        // without a method the value still comes first
+        let wifi = Wifi::new("Test".to_string()).with_raw_auth_type(Some("WPA2".to_string()));
+        assert_eq!(wifi.payload(), "WIFI:T:WPA2;S:Test;;");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn generate_matrix() {
+        let matrix = Wifi::new("Test".to_string()).generate_matrix().unwrap();
+        let width = matrix.len();
+        assert_eq!(width, 21);
+        assert!(matrix.iter().all(|row| row.len() == width));
+
+        // the outer corner, light ring and center of each finder pattern
+        for (x, y) in [(0, 0), (width - 7, 0), (0, width - 7)] {
+            assert!(matrix[y][x]);
+            assert!(matrix[y + 6][x + 6]);
+            assert!(!matrix[y + 1][x + 1]);
+            assert!(matrix[y + 3][x + 3]);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn estimate_qr_version() {
+        let short = Wifi::new("Cafe".to_string()).with_method(Some(WifiMethod::NoPass));
+        let long = Wifi::new("Corporate Network".to_string())
+            .with_method(Some(WifiMethod::Wpa2Enterprise))
+            .with_eap_method(Some(EapMethod::Peap))
+            .with_phase2(Some(Phase2::MsChapV2))
+            .with_anonymous_identity(Some("anonymous@example.com".to_string()))
+            .with_identity(Some("first.last@example.com".to_string()))
+            .with_password(Some("correct horse battery staple".to_string()));
+
+        let version = |wifi: &Wifi| match wifi.estimate_qr_version().unwrap() {
+            qrcode::Version::Normal(version) => version,
+            qrcode::Version::Micro(_) => unreachable!(),
+        };
+        assert!(version(&short) < version(&long));
+
+        // the estimate is the smallest version that fits, and matches the rendered code
+        for wifi in [short, long] {
+            let estimate = version(&wifi);
+            assert!(wifi.payload_fits(qrcode::Version::Normal(estimate), EcLevel::M));
+            assert!(!wifi.payload_fits(qrcode::Version::Normal(estimate - 1), EcLevel::M));
+            assert_eq!(
+                wifi.render_report().unwrap().version,
+                qrcode::Version::Normal(estimate)
+            );
+        }
+    }
+}
@@ -1,15 +1,67 @@
 #![warn(clippy::cargo)]
 
-use clap::Parser;
-use wifi_qr_code_generator::{EapMethod, GenerationError, ImageFormat, Phase2, Wifi, WifiMethod};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use clap::ValueEnum;
+use wifi_qr_code_generator::{
+    generate_batch, generate_batch_ndjson, generate_password, parse_color, validate_batch, Charset,
+    EapMethod, GenerationError, ImageFormat, Phase2, Wifi, WifiMethod,
+};
 
 #[derive(Debug, clap::Parser)]
+#[command(args_conflicts_with_subcommands = true)]
 struct CliArgs {
-    ssid: String,
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Arguments for `generate`, which is the default when no subcommand is given
+    #[command(flatten)]
+    generate: Option<GenerateArgs>,
+    /// Read defaults for the arguments from this TOML file instead of `./wifi-qr.toml`,
+    /// arguments given on the command line take precedence
+    #[cfg(feature = "config")]
+    #[arg(long, global = true, value_name = "PATH")]
+    config: Option<PathBuf>,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    /// Generate the QR-Code image for a network
+    Generate(GenerateArgs),
+    /// Show the fields of a `WIFI:` payload
+    Parse { payload: String },
+    /// Decode the QR-Code in an image, e.g. a photo of a printed code,
+    /// and show the fields of the network if it scans
+    #[cfg(feature = "decode")]
+    Verify { image: PathBuf },
+    /// Generate the images for all networks in a CSV file with `ssid,method,password,hidden` rows
+    Batch(BatchArgs),
+    /// Generate the QR-Code image for a `wifiqr://generate?ssid=...` link
+    #[cfg(feature = "url-handler")]
+    Open { url: String },
+    /// Register this program as the handler for `wifiqr://` links of the current user
+    #[cfg(feature = "url-handler")]
+    RegisterHandler,
+    /// Remove the `wifiqr://` handler registration again
+    #[cfg(feature = "url-handler")]
+    UnregisterHandler,
+}
+
+#[derive(Debug, clap::Args)]
+struct GenerateArgs {
+    #[cfg_attr(not(feature = "interactive"), arg(required = true))]
+    #[cfg_attr(feature = "interactive", arg(required_unless_present = "interactive"))]
+    ssid: Option<String>,
     #[arg(value_enum)]
     kind: Option<WifiMethod>,
     #[arg(long = "hidden")]
     hidden: bool,
+    /// Don't mark the network as hidden, e.g. to turn off `hidden = true` from the config file
+    #[arg(long, overrides_with = "hidden")]
+    no_hidden: bool,
+    /// Keep the WPA2 transition mode of a WPA3 network usable, e.g. while migrating older devices
+    #[arg(long = "no-transition-disable")]
+    no_transition_disable: bool,
     #[arg(long = "eap", value_enum)]
     eap_method: Option<EapMethod>,
     #[arg(long = "ph2", value_enum)]
@@ -18,34 +70,1135 @@ struct CliArgs {
     anonymous_identity: Option<String>,
     #[arg(long = "identity", short = 'i')]
     identity: Option<String>,
-    #[arg(long = "password", short = 'p')]
+    #[arg(long = "password", short = 'p', conflicts_with = "rotate")]
     password: Option<String>,
+    /// Ask for the SSID, method, password and whether the network is hidden instead
+    #[cfg(feature = "interactive")]
+    #[arg(
+        long,
+        conflicts_with_all = ["ssid", "kind", "hidden", "no_hidden", "password", "password_stdin", "password_env", "rotate"]
+    )]
+    interactive: bool,
+    /// Read the password from the first line of stdin, so it doesn't show up in the process list
+    #[arg(long, conflicts_with_all = ["password", "password_env", "rotate"])]
+    password_stdin: bool,
+    /// Read the password from the WIFI_QR_PASSWORD environment variable
+    #[arg(long, conflicts_with_all = ["password", "rotate"])]
+    password_env: bool,
+    /// Format of the image, guessed from the extension of --output when omitted
+    #[arg(long, value_enum)]
+    image_format: Option<ImageFormat>,
+    /// Color of the dark modules, as `#rrggbb`, `#rrggbbaa`, `rgb(r, g, b)`, `rgba(r, g, b, a)`
+    /// or a CSS color name
+    #[arg(long, default_value = "black", value_parser = color)]
+    dark: [u8; 4],
+    /// Color of the light modules and the quiet zone, in the same notations as --dark
+    #[arg(long, default_value = "white", value_parser = color)]
+    light: [u8; 4],
+    /// Swap the colors, i.e. light modules on a dark background
+    #[arg(long)]
+    invert: bool,
+    /// Write the image to PATH instead of `./wifi-<ssid>.png`
+    #[arg(long, short = 'o', value_name = "PATH")]
+    output: Option<PathBuf>,
+    /// Print the code to the terminal, no image is written unless --output is given as well
+    #[arg(long, conflicts_with = "rotate")]
+    stdout_terminal: bool,
+    /// Copy the payload to the clipboard, no image is written unless --output is given as well
+    #[cfg(feature = "clipboard")]
+    #[arg(long, conflicts_with_all = ["rotate", "clipboard_image"])]
+    clipboard: bool,
+    /// Copy the image to the clipboard, no image is written unless --output is given as well
+    #[cfg(feature = "clipboard")]
+    #[arg(long, conflicts_with = "rotate")]
+    clipboard_image: bool,
+    /// Fail instead of overwriting an existing image
+    #[arg(long, conflicts_with = "rotate")]
+    no_clobber: bool,
+    /// Overwrite an existing image, which is the default unless --no-clobber is given
+    #[arg(long, overrides_with = "no_clobber")]
+    force: bool,
+    /// Don't print the payload, which contains the password
+    #[arg(long, short = 'q', visible_alias = "no-print")]
+    quiet: bool,
+    /// Print the payload with the password replaced by `****`
+    #[arg(long, conflicts_with = "quiet")]
+    redact: bool,
+    /// Print the QR version and error correction level used
+    #[arg(long = "report")]
+    report: bool,
+    /// Keep running and regenerate the code with a new random password every MINUTES
+    #[arg(
+        long = "rotate",
+        value_name = "MINUTES",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    rotate: Option<u64>,
+    /// Length of the random passwords generated with --rotate
+    #[arg(long = "password-len", default_value_t = 16, requires = "rotate")]
+    password_len: usize,
+    /// Characters the random passwords generated with --rotate are made of
+    #[arg(
+        long = "password-charset",
+        default_value = "alphanumeric",
+        value_enum,
+        requires = "rotate"
+    )]
+    password_charset: Charset,
+    /// Shell command to run after each rotation, e.g. `--on-rotate "systemctl reload hostapd"`
+    ///
+    /// The command runs with `sh -c` (`cmd /C` on Windows). The new password is passed in the
+    /// WIFI_QR_PASSWORD environment variable, not as an argument, so it stays out of the process list.
+    #[arg(long = "on-rotate", value_name = "CMD", requires = "rotate")]
+    on_rotate: Option<String>,
+}
+
+#[derive(Debug, clap::Args)]
+struct BatchArgs {
+    /// The CSV file, a header row starting with `ssid` is skipped
+    file: PathBuf,
+    /// Directory the images are written to
+    #[arg(long, default_value = ".")]
+    dir: PathBuf,
     #[arg(long, default_value_t, value_enum)]
     image_format: ImageFormat,
+    /// Write one `{"ssid":...,"image":"<base64>"}` line per network to stdout instead of files
+    #[arg(long)]
+    ndjson: bool,
+    /// Add the passwords to the --ndjson output
+    #[arg(long, requires = "ndjson")]
+    include_password: bool,
+}
+
+/// The errors of the library and those only the command line tool runs into
+#[derive(Debug, thiserror::Error)]
+enum CliError {
+    #[error(transparent)]
+    GenerationError(#[from] GenerationError),
+    #[error("{} already exists, pass --force to overwrite it", .0.display())]
+    FileExists(PathBuf),
+    #[cfg(feature = "clipboard")]
+    #[error("clipboard: {0}")]
+    ClipboardError(#[from] arboard::Error),
+    #[cfg(feature = "interactive")]
+    #[error("prompt: {0}")]
+    PromptError(#[from] dialoguer::Error),
+    #[cfg(feature = "config")]
+    #[error("invalid config file: {0}")]
+    ConfigError(#[from] toml::de::Error),
+}
+
+fn main() -> Result<(), CliError> {
+    #[cfg(not(feature = "config"))]
+    let args: CliArgs = clap::Parser::parse();
+    #[cfg(feature = "config")]
+    let args = config::parse()?;
+
+    match args.command {
+        Some(Command::Generate(args)) => generate(args)?,
+        Some(Command::Parse { payload }) => parse(&payload)?,
+        #[cfg(feature = "decode")]
+        Some(Command::Verify { image }) => verify(&image)?,
+        Some(Command::Batch(args)) => batch(args)?,
+        #[cfg(feature = "url-handler")]
+        Some(Command::Open { url }) => {
+            let wifi = Wifi::from_url(&url)?;
+            let file_name = wifi.file_name(None, &ImageFormat::default());
+            println!("{wifi}");
+            wifi.generate_image_file(Some(ImageFormat::default()), file_name.as_ref())?;
+        }
+        #[cfg(feature = "url-handler")]
+        Some(Command::RegisterHandler) => url_handler::register()?,
+        #[cfg(feature = "url-handler")]
+        Some(Command::UnregisterHandler) => url_handler::unregister()?,
+        // clap requires the generate arguments when no subcommand is given
+        None => generate(
+            args.generate
+                .expect("generate arguments without subcommand"),
+        )?,
+    }
+    Ok(())
 }
 
-fn main() -> Result<(), GenerationError> {
-    let args = CliArgs::parse();
+fn generate(args: GenerateArgs) -> Result<(), CliError> {
+    #[cfg(feature = "interactive")]
+    let args = if args.interactive {
+        interactive::Answers::ask()?.apply(args)
+    } else {
+        args
+    };
+
+    #[cfg(feature = "clipboard")]
+    let clipboard = args.clipboard_content();
+    let writes_file = args.writes_file();
+    let password = password(
+        args.password.clone(),
+        args.password_stdin,
+        args.password_env,
+    )?;
+
+    let wifi = args.network(password);
+
+    let file_name = args.output.clone().unwrap_or_else(|| {
+        if let Some(ident) = wifi.identity() {
+            format!("./wifi-{}-{ident}.png", wifi.ssid())
+        } else {
+            format!("./wifi-{}.png", wifi.ssid())
+        }
+        .into()
+    });
 
-    let file_name = if let Some(ident) = &args.identity {
-        format!("./wifi-{}-{ident}.png", args.ssid)
+    let mut claimed = if args.no_clobber && writes_file {
+        Some(ClaimedFile::create(&file_name)?)
     } else {
-        format!("./wifi-{}.png", args.ssid)
+        None
     };
 
-    let wifi = Wifi::new(args.ssid)
-        .with_method(args.kind)
-        .with_hidden(args.hidden)
-        .with_eap_method(args.eap_method)
-        .with_phase2(args.phase2)
-        .with_anonymous_identity(args.anonymous_identity)
-        .with_identity(args.identity)
-        .with_password(args.password);
+    let payload = if args.quiet {
+        PayloadOutput::Quiet
+    } else if args.redact {
+        PayloadOutput::Redacted
+    } else {
+        PayloadOutput::Full
+    };
 
-    let wifi_string = wifi.to_string();
-    println!("{}", wifi_string);
+    if let Some(minutes) = args.rotate {
+        return Ok(rotate(
+            wifi,
+            args.image_format,
+            &file_name,
+            Duration::from_secs(minutes.saturating_mul(60)),
+            || generate_password(args.password_len, args.password_charset),
+            payload,
+            args.on_rotate.as_deref(),
+        )?);
+    }
 
-    wifi.generate_image_file(Some(args.image_format), file_name.as_ref())?;
+    payload.print(&wifi);
 
+    if args.report {
+        println!("{}", wifi.render_report()?);
+    }
+
+    if args.stdout_terminal {
+        print!("{}", wifi.generate_terminal_halfblock()?);
+    }
+
+    #[cfg(feature = "clipboard")]
+    if let Some(content) = clipboard {
+        copy_to_clipboard(&wifi, content)?;
+    }
+
+    if !writes_file {
+        return Ok(());
+    }
+
+    let format = write_code(&wifi, args.image_format, &file_name)?;
+    if let Some(claimed) = &mut claimed {
+        claimed.written = true;
+    }
+    println!("wrote {format} to {}", file_name.display());
     Ok(())
 }
+
+impl GenerateArgs {
+    /// The network described by the arguments, with the password read by [`password`]
+    fn network(&self, password: Option<String>) -> Wifi {
+        let ssid = self
+            .ssid
+            .clone()
+            .expect("clap requires the ssid without --interactive");
+        Wifi::new(ssid)
+            .with_method(self.kind.clone())
+            .with_hidden(self.hidden)
+            .with_transition_disable(!self.no_transition_disable)
+            .with_eap_method(self.eap_method.clone())
+            .with_phase2(self.phase2.clone())
+            .with_anonymous_identity(self.anonymous_identity.clone())
+            .with_identity(self.identity.clone())
+            .with_password(password)
+            .with_colors(self.dark, self.light)
+            .with_inverted(self.invert)
+    }
+
+    /// What to copy to the clipboard, the image wins over the payload
+    #[cfg(feature = "clipboard")]
+    fn clipboard_content(&self) -> Option<ClipboardContent> {
+        if self.clipboard_image {
+            Some(ClipboardContent::Image)
+        } else if self.clipboard {
+            Some(ClipboardContent::Payload)
+        } else {
+            None
+        }
+    }
+
+    /// Whether an image file is written, which is skipped when the code goes
+    /// to the terminal or the clipboard unless --output is given as well
+    fn writes_file(&self) -> bool {
+        #[cfg(feature = "clipboard")]
+        let to_clipboard = self.clipboard_content().is_some();
+        #[cfg(not(feature = "clipboard"))]
+        let to_clipboard = false;
+
+        !(self.stdout_terminal || to_clipboard) || self.output.is_some()
+    }
+}
+
+/// The image file created up front for --no-clobber, removed again if generating fails
+struct ClaimedFile<'a> {
+    path: &'a Path,
+    written: bool,
+}
+
+impl<'a> ClaimedFile<'a> {
+    /// Create the empty file, failing if it exists,
+    /// so no other process can create it between the check and writing the image
+    fn create(path: &'a Path) -> Result<Self, CliError> {
+        match std::fs::File::create_new(path) {
+            Ok(_) => Ok(Self {
+                path,
+                written: false,
+            }),
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                Err(CliError::FileExists(path.to_path_buf()))
+            }
+            Err(err) => Err(GenerationError::from(err).into()),
+        }
+    }
+}
+
+impl Drop for ClaimedFile<'_> {
+    fn drop(&mut self) {
+        if !self.written {
+            let _ = std::fs::remove_file(self.path);
+        }
+    }
+}
+
+/// What --clipboard and --clipboard-image copy
+#[cfg(feature = "clipboard")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClipboardContent {
+    Payload,
+    Image,
+}
+
+/// Copy the payload or the rendered code to the clipboard
+#[cfg(feature = "clipboard")]
+fn copy_to_clipboard(wifi: &Wifi, content: ClipboardContent) -> Result<(), CliError> {
+    let mut clipboard = arboard::Clipboard::new()?;
+    match content {
+        ClipboardContent::Image => {
+            let image = wifi.generate_rgba_image()?;
+            clipboard.set_image(arboard::ImageData {
+                width: image.width() as usize,
+                height: image.height() as usize,
+                bytes: image.into_raw().into(),
+            })?;
+            println!("copied the image to the clipboard");
+        }
+        ClipboardContent::Payload => {
+            clipboard.set_text(wifi.payload())?;
+            println!("copied the payload to the clipboard");
+        }
+    }
+    Ok(())
+}
+
+/// Write the code to `file_path`, guessing the format from the extension if none is given,
+/// returns the name of the format that was written
+fn write_code(
+    wifi: &Wifi,
+    image_format: Option<ImageFormat>,
+    file_path: &Path,
+) -> Result<String, GenerationError> {
+    #[cfg(feature = "svg")]
+    if image_format.is_none() && file_path.extension().is_some_and(|ext| ext == "svg") {
+        wifi.generate_svg_file(file_path)?;
+        return Ok("SVG".to_string());
+    }
+    let format = wifi.generate_image_file(image_format, file_path)?;
+    Ok(format!("{format:?}").to_uppercase())
+}
+
+fn parse(payload: &str) -> Result<(), GenerationError> {
+    let wifi: Wifi = payload.parse()?;
+    print!("{}", describe(&wifi));
+    Ok(())
+}
+
+#[cfg(feature = "decode")]
+fn verify(image: &Path) -> Result<(), GenerationError> {
+    let wifi = Wifi::from_image(image)?;
+    println!("{} scans", image.display());
+    print!("{}", describe(&wifi));
+    Ok(())
+}
+
+/// The fields of the network, one `name: value` line each
+fn describe(wifi: &Wifi) -> String {
+    use std::fmt::Write;
+
+    let mut out = String::new();
+    writeln!(out, "ssid: {}", wifi.ssid()).unwrap();
+    if let Some(method) = wifi.method() {
+        writeln!(out, "method: {method:?}").unwrap();
+    }
+    if let Some(auth_type) = wifi.raw_auth_type() {
+        writeln!(out, "auth type: {auth_type}").unwrap();
+    }
+    writeln!(out, "hidden: {}", wifi.is_hidden()).unwrap();
+    if let Some(eap) = wifi.eap_method() {
+        writeln!(out, "eap: {eap:?}").unwrap();
+    }
+    if let Some(phase2) = wifi.phase2() {
+        writeln!(out, "phase 2: {phase2:?}").unwrap();
+    }
+    if let Some(anonymous_identity) = wifi.anonymous_identity() {
+        writeln!(out, "anonymous identity: {anonymous_identity}").unwrap();
+    }
+    if let Some(identity) = wifi.identity() {
+        writeln!(out, "identity: {identity}").unwrap();
+    }
+    if let Some(password) = wifi.password() {
+        writeln!(out, "password: {password}").unwrap();
+    }
+    if let Some(psk) = wifi.psk() {
+        let psk: String = psk.iter().map(|b| format!("{b:02x}")).collect();
+        writeln!(out, "psk: {psk}").unwrap();
+    }
+    if let Some(public_key) = wifi.public_key() {
+        writeln!(out, "public key: {} bytes", public_key.len()).unwrap();
+    }
+
+    out
+}
+
+fn batch(args: BatchArgs) -> Result<(), GenerationError> {
+    let csv = std::fs::read_to_string(&args.file)?;
+
+    let mut failed = 0;
+    let mut lines = Vec::new();
+    let mut networks = Vec::new();
+    for (index, line) in csv.lines().enumerate() {
+        if line.trim().is_empty() || (index == 0 && line.starts_with("ssid")) {
+            continue;
+        }
+        match parse_row(line) {
+            Ok(wifi) => {
+                lines.push(index + 1);
+                networks.push(wifi);
+            }
+            Err(err) => {
+                eprintln!("line {}: {err}", index + 1);
+                failed += 1;
+            }
+        }
+    }
+
+    // skip the networks devices would reject, before generating anything
+    let invalid = validate_batch(&networks);
+    for (index, err) in &invalid {
+        eprintln!("line {}: {err}", lines[*index]);
+    }
+    for (index, _) in invalid.iter().rev() {
+        lines.remove(*index);
+        networks.remove(*index);
+    }
+    failed += invalid.len();
+    let total = failed + networks.len();
+
+    if args.ndjson {
+        let results = generate_batch_ndjson(
+            &networks,
+            args.image_format,
+            args.include_password,
+            std::io::stdout().lock(),
+        );
+        for (line, result) in lines.iter().zip(results) {
+            if let Err(err) = result {
+                eprintln!("line {line}: {err}");
+                failed += 1;
+            }
+        }
+    } else {
+        let results = generate_batch(networks.into_iter(), &args.dir, args.image_format);
+        for (line, result) in lines.iter().zip(results) {
+            match result {
+                Ok(path) => println!("{}", path.display()),
+                Err(err) => {
+                    eprintln!("line {line}: {err}");
+                    failed += 1;
+                }
+            }
+        }
+    }
+
+    eprintln!("generated {} of {total} codes", total - failed);
+    if failed > 0 {
+        return Err(std::io::Error::other(format!("{failed} of {total} codes failed")).into());
+    }
+    Ok(())
+}
+
+/// A `ssid,method,password,hidden` row, all but the SSID may be empty or missing
+fn parse_row(line: &str) -> Result<Wifi, String> {
+    let fields = csv_fields(line)?;
+    let field = |index: usize| {
+        fields
+            .get(index)
+            .map(|field| field.trim())
+            .filter(|field| !field.is_empty())
+    };
+
+    if fields.len() > 4 {
+        return Err(format!(
+            "expected at most 4 columns, found {}",
+            fields.len()
+        ));
+    }
+    let ssid = field(0).ok_or("missing ssid")?;
+    let method = field(1)
+        .map(|method| {
+            WifiMethod::from_str(method, true).map_err(|_| format!("unknown method `{method}`"))
+        })
+        .transpose()?;
+    let hidden = match field(3).map(str::to_ascii_lowercase).as_deref() {
+        None | Some("false" | "0" | "no") => false,
+        Some("true" | "1" | "yes") => true,
+        Some(hidden) => return Err(format!("invalid hidden value `{hidden}`")),
+    };
+    // unlike the other columns the password isn't trimmed, spaces may be part of it
+    let password = fields.get(2).filter(|password| !password.is_empty());
+
+    Ok(Wifi::new(ssid.to_string())
+        .with_method(method)
+        .with_password(password.cloned())
+        .with_hidden(hidden))
+}
+
+/// Split a CSV line, fields may be quoted with `"` and contain `""` for a quote
+fn csv_fields(line: &str) -> Result<Vec<String>, String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let field = fields.last_mut().expect("there is always a current field");
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => fields.push(String::new()),
+            c => field.push(c),
+        }
+    }
+
+    if quoted {
+        return Err("unterminated quote".to_string());
+    }
+    Ok(fields)
+}
+
+fn password(
+    password: Option<String>,
+    from_stdin: bool,
+    from_env: bool,
+) -> Result<Option<String>, GenerationError> {
+    if from_stdin {
+        let mut line = String::new();
+        if std::io::stdin().read_line(&mut line)? == 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "expected the password on stdin",
+            )
+            .into());
+        }
+        let len = line.trim_end_matches(['\r', '\n']).len();
+        line.truncate(len);
+        Ok(Some(line))
+    } else if from_env {
+        std::env::var("WIFI_QR_PASSWORD").map(Some).map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "WIFI_QR_PASSWORD is not set or not valid unicode",
+            )
+            .into()
+        })
+    } else {
+        Ok(password)
+    }
+}
+
+fn color(value: &str) -> Result<[u8; 4], GenerationError> {
+    parse_color(value).map(|color| color.0)
+}
+
+/// How the `WIFI:` payload is printed
+#[derive(Debug, Clone, Copy)]
+enum PayloadOutput {
+    Full,
+    Redacted,
+    Quiet,
+}
+
+impl PayloadOutput {
+    fn print(self, wifi: &Wifi) {
+        match self {
+            PayloadOutput::Full => println!("{wifi}"),
+            PayloadOutput::Redacted => println!("{}", wifi.to_redacted_string()),
+            PayloadOutput::Quiet => {}
+        }
+    }
+}
+
+fn rotate(
+    wifi: Wifi,
+    image_format: Option<ImageFormat>,
+    file_path: &Path,
+    interval: Duration,
+    new_password: impl Fn() -> Result<String, GenerationError>,
+    payload: PayloadOutput,
+    on_rotate: Option<&str>,
+) -> Result<(), GenerationError> {
+    // keep the extension, the format may be guessed from it
+    let mut tmp_name = std::ffi::OsString::from(".tmp-");
+    tmp_name.push(file_path.file_name().unwrap_or_default());
+    let tmp_path = file_path.with_file_name(tmp_name);
+
+    loop {
+        let password = new_password()?;
+        let wifi = wifi.clone().with_password(Some(password.clone()));
+
+        payload.print(&wifi);
+
+        // write next to the target and rename, so readers never see a partially written image
+        write_code(&wifi, image_format.clone(), &tmp_path)?;
+        std::fs::rename(&tmp_path, file_path)?;
+
+        if let Some(command) = on_rotate {
+            let status = shell_command(command)
+                .env("WIFI_QR_PASSWORD", &password)
+                .status()?;
+            if !status.success() {
+                eprintln!("{command} exited with {status}");
+            }
+        }
+
+        std::thread::sleep(interval);
+    }
+}
+
+/// Run `command` through the shell, so it can have arguments, pipes and the like
+fn shell_command(command: &str) -> std::process::Command {
+    #[cfg(windows)]
+    let (shell, flag) = ("cmd", "/C");
+    #[cfg(not(windows))]
+    let (shell, flag) = ("sh", "-c");
+
+    let mut shell = std::process::Command::new(shell);
+    shell.args([flag, command]);
+    shell
+}
+
+/// Prompts for `generate --interactive`
+#[cfg(feature = "interactive")]
+mod interactive {
+    use clap::ValueEnum;
+    use dialoguer::{Confirm, Input, Password, Select};
+    use wifi_qr_code_generator::WifiMethod;
+
+    use crate::{CliError, GenerateArgs};
+
+    /// The answers to the prompts, they take the place of the corresponding arguments
+    pub struct Answers {
+        pub ssid: String,
+        pub method: WifiMethod,
+        pub password: Option<String>,
+        pub hidden: bool,
+    }
+
+    impl Answers {
+        pub fn ask() -> Result<Self, CliError> {
+            let ssid = Input::new().with_prompt("SSID").interact_text()?;
+
+            let methods = WifiMethod::value_variants();
+            let items: Vec<String> = methods
+                .iter()
+                .enumerate()
+                .map(|(index, method)| {
+                    let name = method.to_possible_value().expect("no method is skipped");
+                    format!("{}. {}", index + 1, name.get_name())
+                })
+                .collect();
+            let default = methods
+                .iter()
+                .position(|method| *method == WifiMethod::Wpa)
+                .unwrap_or_default();
+            let method = methods[Select::new()
+                .with_prompt("Method")
+                .items(&items)
+                .default(default)
+                .interact()?]
+            .clone();
+
+            let password = match method {
+                WifiMethod::NoPass | WifiMethod::Owe => None,
+                _ => Some(Password::new().with_prompt("Password").interact()?),
+            };
+
+            let hidden = Confirm::new()
+                .with_prompt("Hidden network?")
+                .default(false)
+                .interact()?;
+
+            Ok(Answers {
+                ssid,
+                method,
+                password,
+                hidden,
+            })
+        }
+
+        pub fn apply(self, args: GenerateArgs) -> GenerateArgs {
+            GenerateArgs {
+                ssid: Some(self.ssid),
+                kind: Some(self.method),
+                password: self.password,
+                hidden: self.hidden,
+                ..args
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use wifi_qr_code_generator::Wifi;
+
+        use super::*;
+        use crate::CliArgs;
+
+        #[test]
+        fn answers_replace_arguments() {
+            let answers = Answers {
+                ssid: "Guest".to_string(),
+                method: WifiMethod::Wpa2,
+                password: Some("password".to_string()),
+                hidden: true,
+            };
+            let args: CliArgs = clap::Parser::try_parse_from([
+                "wifi-qr-code-generator",
+                "--interactive",
+                "--invert",
+            ])
+            .unwrap();
+            let args = answers.apply(args.generate.unwrap());
+            // the arguments without a prompt are kept
+            assert!(args.invert);
+
+            let expected = Wifi::new("Guest".to_string())
+                .with_method(Some(WifiMethod::Wpa2))
+                .with_password(Some("password".to_string()))
+                .with_hidden(true)
+                .with_inverted(true);
+            assert_eq!(args.network(args.password.clone()), expected);
+        }
+    }
+}
+
+/// Defaults for the `generate` arguments from a `wifi-qr.toml`
+///
+/// The keys are named like the fields of [`GenerateArgs`], e.g. `eap_method = "peap"`,
+/// with the values spelled as on the command line.
+///
+/// An argument given on the command line always wins over the file,
+/// which in turn wins over the defaults of the arguments.
+/// Flags are added up instead: `hidden = true` can only be turned off again with `--no-hidden`
+/// and `no_clobber = true` with `--force`, the other flags can't be turned off.
+#[cfg(feature = "config")]
+mod config {
+    use std::path::Path;
+
+    use clap::parser::ValueSource;
+    use clap::{ArgMatches, CommandFactory, FromArgMatches, ValueEnum};
+    use serde::{Deserialize, Deserializer};
+    use wifi_qr_code_generator::{EapMethod, GenerationError, ImageFormat, Phase2, WifiMethod};
+
+    use crate::{CliArgs, CliError, Command, GenerateArgs};
+
+    /// Looked up in the current directory when --config is not given
+    const FILE_NAME: &str = "wifi-qr.toml";
+
+    #[derive(Debug, Default, Deserialize)]
+    #[serde(default, deny_unknown_fields)]
+    struct Config {
+        #[serde(deserialize_with = "value_enum")]
+        kind: Option<WifiMethod>,
+        hidden: bool,
+        no_transition_disable: bool,
+        #[serde(deserialize_with = "value_enum")]
+        eap_method: Option<EapMethod>,
+        #[serde(deserialize_with = "value_enum")]
+        phase2: Option<Phase2>,
+        anonymous_identity: Option<String>,
+        identity: Option<String>,
+        #[serde(deserialize_with = "value_enum")]
+        image_format: Option<ImageFormat>,
+        #[serde(deserialize_with = "color")]
+        dark: Option<[u8; 4]>,
+        #[serde(deserialize_with = "color")]
+        light: Option<[u8; 4]>,
+        invert: bool,
+        no_clobber: bool,
+        report: bool,
+    }
+
+    fn value_enum<'de, D: Deserializer<'de>, T: ValueEnum>(
+        deserializer: D,
+    ) -> Result<Option<T>, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        T::from_str(&value, true)
+            .map(Some)
+            .map_err(serde::de::Error::custom)
+    }
+
+    fn color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<[u8; 4]>, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        crate::color(&value)
+            .map(Some)
+            .map_err(serde::de::Error::custom)
+    }
+
+    /// Parse the command line and fill in the arguments it leaves out from the config file
+    pub fn parse() -> Result<CliArgs, CliError> {
+        let matches = CliArgs::command().get_matches();
+        let mut args = CliArgs::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+
+        let config = match &args.config {
+            Some(path) => Config::load(path)?,
+            None if Path::new(FILE_NAME).exists() => Config::load(Path::new(FILE_NAME))?,
+            None => return Ok(args),
+        };
+
+        match (&mut args.command, &mut args.generate) {
+            (Some(Command::Generate(generate)), _) => {
+                let matches = matches
+                    .subcommand_matches("generate")
+                    .expect("generate subcommand was parsed");
+                config.merge(generate, matches);
+            }
+            (None, Some(generate)) => config.merge(generate, &matches),
+            _ => {}
+        }
+        Ok(args)
+    }
+
+    impl Config {
+        fn load(path: &Path) -> Result<Self, CliError> {
+            let config = std::fs::read_to_string(path).map_err(GenerationError::from)?;
+            Ok(toml::from_str(&config)?)
+        }
+
+        /// Fill in the arguments that were not given on the command line
+        fn merge(self, args: &mut GenerateArgs, matches: &ArgMatches) {
+            let from_command_line =
+                |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+
+            args.kind = args.kind.take().or(self.kind);
+            args.hidden |= self.hidden && !args.no_hidden;
+            args.no_transition_disable |= self.no_transition_disable;
+            args.eap_method = args.eap_method.take().or(self.eap_method);
+            args.phase2 = args.phase2.take().or(self.phase2);
+            args.anonymous_identity = args.anonymous_identity.take().or(self.anonymous_identity);
+            args.identity = args.identity.take().or(self.identity);
+            args.image_format = args.image_format.take().or(self.image_format);
+            if let Some(dark) = self.dark.filter(|_| !from_command_line("dark")) {
+                args.dark = dark;
+            }
+            if let Some(light) = self.light.filter(|_| !from_command_line("light")) {
+                args.light = light;
+            }
+            args.invert |= self.invert;
+            args.no_clobber |= self.no_clobber && !args.force;
+            args.report |= self.report;
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        /// The generate arguments of `args` with `config` filled in
+        fn merged(config: &str, args: &[&str]) -> GenerateArgs {
+            let matches = CliArgs::command()
+                .try_get_matches_from(["wifi-qr-code-generator"].iter().chain(args))
+                .unwrap();
+            let mut generate = CliArgs::from_arg_matches(&matches)
+                .unwrap()
+                .generate
+                .unwrap();
+            let config: Config = toml::from_str(config).unwrap();
+            config.merge(&mut generate, &matches);
+            generate
+        }
+
+        #[test]
+        fn merge() {
+            let config = r#"
+                kind = "wpa2-enterprise"
+                eap_method = "peap"
+                identity = "default"
+                hidden = true
+            "#;
+
+            let args = merged(config, &["Office", "--identity", "user"]);
+            assert_eq!(args.ssid.as_deref(), Some("Office"));
+            assert_eq!(args.kind, Some(WifiMethod::Wpa2Enterprise));
+            assert_eq!(args.eap_method, Some(EapMethod::Peap));
+            // the command line wins
+            assert_eq!(args.identity.as_deref(), Some("user"));
+            assert!(args.hidden);
+
+            let args = merged(config, &["Office", "--no-hidden"]);
+            assert!(!args.hidden);
+            assert_eq!(args.identity.as_deref(), Some("default"));
+        }
+    }
+}
+
+/// Registration of the `wifiqr://` scheme with the desktop environment, for the current user only
+#[cfg(feature = "url-handler")]
+mod url_handler {
+    use wifi_qr_code_generator::GenerationError;
+
+    #[cfg(target_os = "linux")]
+    const DESKTOP_FILE: &str = "wifiqr-handler.desktop";
+
+    #[cfg(target_os = "linux")]
+    fn desktop_file_path() -> Result<std::path::PathBuf, GenerationError> {
+        let data_home = std::env::var_os("XDG_DATA_HOME")
+            .map(std::path::PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME")
+                    .map(|home| std::path::Path::new(&home).join(".local/share"))
+            })
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    "neither XDG_DATA_HOME nor HOME is set",
+                )
+            })?;
+        Ok(data_home.join("applications").join(DESKTOP_FILE))
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn register() -> Result<(), GenerationError> {
+        let exe = std::env::current_exe()?;
+        let path = desktop_file_path()?;
+        std::fs::create_dir_all(path.parent().expect("desktop file is in a directory"))?;
+        std::fs::write(
+            &path,
+            format!(
+                "[Desktop Entry]\nType=Application\nName=Wifi QR-Code Generator\nExec=\"{}\" open %u\nNoDisplay=true\nMimeType=x-scheme-handler/wifiqr;\n",
+                exe.display()
+            ),
+        )?;
+        run(
+            "xdg-mime",
+            &["default", DESKTOP_FILE, "x-scheme-handler/wifiqr"],
+        )
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn unregister() -> Result<(), GenerationError> {
+        match std::fs::remove_file(desktop_file_path()?) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    #[cfg(windows)]
+    const KEY: &str = r"HKCU\Software\Classes\wifiqr";
+
+    #[cfg(windows)]
+    pub fn register() -> Result<(), GenerationError> {
+        let exe = std::env::current_exe()?;
+        let command = format!("\"{}\" open \"%1\"", exe.display());
+        run(
+            "reg",
+            &["add", KEY, "/ve", "/d", "URL:wifiqr Protocol", "/f"],
+        )?;
+        run("reg", &["add", KEY, "/v", "URL Protocol", "/d", "", "/f"])?;
+        run(
+            "reg",
+            &[
+                "add",
+                &format!(r"{KEY}\shell\open\command"),
+                "/ve",
+                "/d",
+                &command,
+                "/f",
+            ],
+        )
+    }
+
+    #[cfg(windows)]
+    pub fn unregister() -> Result<(), GenerationError> {
+        run("reg", &["delete", KEY, "/f"])
+    }
+
+    /// macOS only routes URL schemes to app bundles declaring them in their `Info.plist`,
+    /// a plain binary can't register itself
+    #[cfg(not(any(target_os = "linux", windows)))]
+    pub fn register() -> Result<(), GenerationError> {
+        Err(unsupported())
+    }
+
+    #[cfg(not(any(target_os = "linux", windows)))]
+    pub fn unregister() -> Result<(), GenerationError> {
+        Err(unsupported())
+    }
+
+    #[cfg(not(any(target_os = "linux", windows)))]
+    fn unsupported() -> GenerationError {
+        std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "registering a URL handler is only supported on Linux and Windows",
+        )
+        .into()
+    }
+
+    #[cfg(any(target_os = "linux", windows))]
+    fn run(program: &str, args: &[&str]) -> Result<(), GenerationError> {
+        let status = std::process::Command::new(program).args(args).status()?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(std::io::Error::other(format!("{program} exited with {status}")).into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generate_args(args: &[&str]) -> GenerateArgs {
+        let args: CliArgs =
+            clap::Parser::try_parse_from(["wifi-qr-code-generator", "Test"].iter().chain(args))
+                .unwrap();
+        args.generate.unwrap()
+    }
+
+    fn command(args: &[&str]) -> Option<Command> {
+        let args: CliArgs =
+            clap::Parser::try_parse_from(["wifi-qr-code-generator"].iter().chain(args)).unwrap();
+        args.command
+    }
+
+    #[test]
+    fn writes_file() {
+        assert!(generate_args(&[]).writes_file());
+        assert!(!generate_args(&["--stdout-terminal"]).writes_file());
+        assert!(generate_args(&["--stdout-terminal", "--output", "x.png"]).writes_file());
+    }
+
+    #[cfg(feature = "clipboard")]
+    #[test]
+    fn clipboard_content() {
+        assert_eq!(generate_args(&[]).clipboard_content(), None);
+
+        let args = generate_args(&["--clipboard"]);
+        assert_eq!(args.clipboard_content(), Some(ClipboardContent::Payload));
+        assert!(!args.writes_file());
+
+        let args = generate_args(&["--clipboard-image"]);
+        assert_eq!(args.clipboard_content(), Some(ClipboardContent::Image));
+        assert!(!args.writes_file());
+
+        let args = generate_args(&["--clipboard-image", "--output", "x.png"]);
+        assert_eq!(args.clipboard_content(), Some(ClipboardContent::Image));
+        assert!(args.writes_file());
+    }
+    #[test]
+    fn generate_subcommand() {
+        let path = std::env::temp_dir().join(format!("wifi-qr-cli-{}.png", std::process::id()));
+        let output = path.to_str().unwrap();
+
+        let Some(Command::Generate(args)) = command(&["generate", "Test", "--output", output])
+        else {
+            panic!("expected the generate subcommand");
+        };
+        generate(args).unwrap();
+        assert!(image::open(&path).is_ok());
+        std::fs::remove_file(&path).unwrap();
+
+        // generate is the default
+        assert!(command(&["Test", "--output", output]).is_none());
+        generate(generate_args(&["--output", output])).unwrap();
+        assert!(image::open(&path).is_ok());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn parse_subcommand() {
+        let Some(Command::Parse { payload }) =
+            command(&["parse", "WIFI:T:WPA;S:Test;P:pass;H:true;;"])
+        else {
+            panic!("expected the parse subcommand");
+        };
+        parse(&payload).unwrap();
+        assert_eq!(
+            describe(&payload.parse().unwrap()),
+            "ssid: Test\nmethod: Wpa\nhidden: true\npassword: pass\n"
+        );
+        assert!(parse("S:Test;;").is_err());
+
+        // parse only reads the payload, there is no image to write
+        let with_format: Result<CliArgs, _> = clap::Parser::try_parse_from([
+            "wifi-qr-code-generator",
+            "parse",
+            "WIFI:S:Test;;",
+            "--image-format",
+            "png",
+        ]);
+        assert!(with_format.is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn on_rotate_command() {
+        let status = shell_command(r#"test "$WIFI_QR_PASSWORD" = "pass word" && exit 3"#)
+            .env("WIFI_QR_PASSWORD", "pass word")
+            .status()
+            .unwrap();
+        assert_eq!(status.code(), Some(3));
+    }
+
+    #[cfg(feature = "decode")]
+    #[test]
+    fn verify_subcommand() {
+        let path = std::env::temp_dir().join(format!("wifi-qr-verify-{}.png", std::process::id()));
+        let Some(Command::Verify { image }) = command(&["verify", path.to_str().unwrap()]) else {
+            panic!("expected the verify subcommand");
+        };
+
+        image::GrayImage::from_pixel(64, 64, image::Luma([255]))
+            .save(&path)
+            .unwrap();
+        let blank = verify(&image);
+
+        Wifi::new("Test".to_string())
+            .generate_image_file(None, &path)
+            .unwrap();
+        let generated = verify(&image);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(blank, Err(GenerationError::NoCodeFound)));
+        assert!(generated.is_ok());
+    }
+}
@@ -0,0 +1,327 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use base64::Engine;
+
+use crate::url::{parse_eap, parse_phase2};
+#[cfg(feature = "std")]
+use crate::Field;
+use crate::{GenerationError, Wifi, WifiMethod};
+
+const PREFIX: &str = "WIFI:";
+
+/// Parse a `WIFI:` payload, as generated by [`Wifi::payload`] or read from a scanned code.
+///
+/// A leading byte order mark is ignored and the payload may end in `;` or `;;`.
+/// Unknown `T` values are kept with [`Wifi::with_raw_auth_type`],
+/// an unquoted 64 digit hex `P` is read as a raw PSK (see [`Wifi::with_raw_psk`])
+/// and fields the crate doesn't model are kept with [`Wifi::with_raw_field`].
+impl FromStr for Wifi {
+    type Err = GenerationError;
+
+    fn from_str(payload: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| GenerationError::InvalidPayload(reason.to_string());
+
+        let fields = payload_fields(payload)
+            .ok_or_else(|| invalid("expected the payload to start with `WIFI:`"))?;
+
+        let mut ssid = None;
+        let mut wifi = Wifi::new(String::new());
+        let mut transition_disable = false;
+
+        for field in split_unescaped(fields, ';').filter(|field| !field.is_empty()) {
+            let (name, value) = split_unescaped(field, ':')
+                .next()
+                .filter(|name| name.len() < field.len())
+                .map(|name| (name, &field[name.len() + 1..]))
+                .ok_or_else(|| invalid(&format!("field `{field}` has no value")))?;
+            let (value, quoted) = unescape(value)
+                .ok_or_else(|| invalid(&format!("field `{name}` ends in a lone backslash")))?;
+
+            match name {
+                "T" => match value.as_str() {
+                    "nopass" => wifi.kind = Some(WifiMethod::NoPass),
+                    "WEP" => wifi.kind = Some(WifiMethod::Wep),
+                    "WPA" => wifi.kind = Some(WifiMethod::Wpa),
+                    "WPA2-EAP" => wifi.kind = Some(WifiMethod::Wpa2Enterprise),
+                    _ => wifi.raw_auth_type = Some(value),
+                },
+                "R" => {
+                    let flags = u32::from_str_radix(&value, 16)
+                        .map_err(|_| invalid(&format!("`R` is not hex: {value}")))?;
+                    // bit 0 disables the WPA2 transition mode of WPA3-Personal
+                    transition_disable = flags & 1 == 1;
+                }
+                "S" => ssid = Some(value),
+                "H" => {
+                    wifi.hidden = match value.to_ascii_lowercase().as_str() {
+                        "true" => true,
+                        "false" => false,
+                        _ => {
+                            return Err(invalid(&format!("`H` is neither true nor false: {value}")))
+                        }
+                    }
+                }
+                "E" => {
+                    wifi.eap_method = Some(
+                        parse_eap(&value)
+                            .ok_or_else(|| invalid(&format!("unknown EAP method `{value}`")))?,
+                    )
+                }
+                "PH2" => {
+                    wifi.phase2 = Some(
+                        parse_phase2(&value)
+                            .ok_or_else(|| invalid(&format!("unknown phase 2 method `{value}`")))?,
+                    )
+                }
+                "A" => wifi.anonymous_identity = Some(value),
+                "I" => wifi.identity = Some(value),
+                // quotes mark a passphrase that would otherwise be read as hex
+                "P" => match parse_psk(&value) {
+                    Some(psk) if !quoted => wifi = wifi.with_psk_hex(psk),
+                    _ => wifi = wifi.with_password(Some(value)),
+                },
+                "K" => {
+                    wifi.public_key = Some(
+                        base64::engine::general_purpose::STANDARD
+                            .decode(&value)
+                            .map_err(|_| invalid(&format!("`K` is not base64: {value}")))?,
+                    )
+                }
+                _ => {
+                    let (name, _) = unescape(name).ok_or_else(|| {
+                        invalid(&format!("field name `{name}` ends in a lone backslash"))
+                    })?;
+                    wifi.raw_fields.push((name, value));
+                }
+            }
+        }
+
+        if transition_disable {
+            match wifi.kind {
+                Some(WifiMethod::Wpa) | None => wifi.kind = Some(WifiMethod::Wpa3),
+                _ => return Err(invalid("`R` is only supported for WPA networks")),
+            }
+        }
+
+        wifi.ssid = ssid.ok_or_else(|| invalid("missing `S` field"))?;
+        Ok(wifi)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Wifi {
+    /// Fill the `{name}` placeholders of a `WIFI:` payload template from `values` and parse the result,
+    /// e.g. `WIFI:T:WPA;S:Event;P:{pw};;` to generate codes for a list of passwords.
+    ///
+    /// Values are escaped, so they can't add or end fields.
+    /// A value filling a whole field is quoted like [`Wifi::payload`] does when needed.
+    pub fn from_template(
+        template: &str,
+        values: &HashMap<String, String>,
+    ) -> Result<Self, GenerationError> {
+        let mut payload = String::with_capacity(template.len());
+        let mut rest = template;
+
+        while let Some(start) = rest.find('{') {
+            let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+                break;
+            };
+            let name = &rest[start + 1..end];
+            let value = values.get(name).ok_or_else(|| {
+                GenerationError::InvalidPayload(format!("no value for placeholder `{{{name}}}`"))
+            })?;
+
+            payload.push_str(&rest[..start]);
+            let whole_field = payload.ends_with(':') && rest[end + 1..].starts_with(';');
+            if whole_field {
+                payload.push_str(&Field::escape_field_value(value));
+            } else {
+                payload.push_str(&Field::escape(value));
+            }
+            rest = &rest[end + 1..];
+        }
+        payload.push_str(rest);
+
+        payload.parse()
+    }
+}
+
+/// The fields after the `WIFI:` prefix, ignoring a leading byte order mark and trailing line breaks,
+/// `None` if there is no prefix
+pub(crate) fn payload_fields(payload: &str) -> Option<&str> {
+    let payload = payload.strip_prefix('\u{feff}').unwrap_or(payload);
+    payload.trim_end_matches(['\r', '\n']).strip_prefix(PREFIX)
+}
+
+/// Split at each `delimiter` that isn't escaped with a backslash
+fn split_unescaped(value: &str, delimiter: char) -> impl Iterator<Item = &str> {
+    let mut escaped = false;
+    value.split(move |c| {
+        let split = !escaped && c == delimiter;
+        escaped = !escaped && c == '\\';
+        split
+    })
+}
+
+/// Undo [`Field::escape_field_value`], returns whether the value was quoted
+/// or `None` if it ends in an unpaired backslash
+fn unescape(value: &str) -> Option<(String, bool)> {
+    let quoted = value.len() >= 2
+        && value.starts_with('"')
+        && value.ends_with('"')
+        // the closing quote isn't an escaped one
+        && value[..value.len() - 1]
+            .chars()
+            .rev()
+            .take_while(|&c| c == '\\')
+            .count()
+            % 2
+            == 0;
+    let value = if quoted {
+        &value[1..value.len() - 1]
+    } else {
+        value
+    };
+
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        unescaped.push(if c == '\\' { chars.next()? } else { c });
+    }
+    Some((unescaped, quoted))
+}
+
+pub(crate) fn parse_psk(value: &str) -> Option<[u8; 32]> {
+    if value.len() != 64 || !value.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let mut psk = [0; 32];
+    for (byte, digits) in psk.iter_mut().zip(value.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(core::str::from_utf8(digits).ok()?, 16).ok()?;
+    }
+    Some(psk)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn byte_order_mark() {
+        let wifi: Wifi = "\u{feff}WIFI:T:WPA;S:Test;P:password;;".parse().unwrap();
+        assert_eq!(wifi.ssid, "Test");
+        assert_eq!(wifi.password.as_deref(), Some("password"));
+        assert_eq!(
+            payload_fields("\u{feff}WIFI:S:Test;;\r\n"),
+            Some("S:Test;;")
+        );
+        // only in front of the prefix
+        assert_eq!(
+            payload_fields("WIFI:\u{feff}S:Test;;"),
+            Some("\u{feff}S:Test;;")
+        );
+        assert_eq!(payload_fields("\u{feff}S:Test;;"), None);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn template() {
+        let values = HashMap::from([
+            ("pw".to_string(), "pass;word:1".to_string()),
+            ("name".to_string(), "Event".to_string()),
+        ]);
+        let wifi = Wifi::from_template("WIFI:T:WPA;S:{name} 2024;P:{pw};;", &values).unwrap();
+
+        assert_eq!(wifi.ssid, "Event 2024");
+        // the semicolon stays in the password instead of ending the field
+        assert_eq!(wifi.password.as_deref(), Some("pass;word:1"));
+        assert_eq!(wifi.kind, Some(WifiMethod::Wpa));
+        assert_eq!(wifi.payload(), r"WIFI:T:WPA;S:Event 2024;P:pass\;word\:1;;");
+
+        assert!(matches!(
+            Wifi::from_template("WIFI:S:{ssid};;", &values),
+            Err(GenerationError::InvalidPayload(_))
+        ));
+    }
+
+    #[test]
+    fn round_trip() {
+        let networks = [
+            Wifi::new("Open".to_string()).with_method(Some(WifiMethod::NoPass)),
+            Wifi::new("Hidden; \"quoted\"".to_string())
+                .with_method(Some(WifiMethod::Wep))
+                .with_password(Some("abcde".to_string()))
+                .with_hidden(true),
+            Wifi::new("Home".to_string())
+                .with_method(Some(WifiMethod::Wpa))
+                .with_password(Some(r"back\slash:colon,comma;".to_string())),
+            Wifi::new("Home".to_string())
+                .with_method(Some(WifiMethod::Wpa))
+                .with_password(Some("ab".repeat(32))),
+            Wifi::new("Home".to_string())
+                .with_method(Some(WifiMethod::Wpa))
+                .with_psk_hex([0x5a; 32]),
+            Wifi::new("Modern".to_string())
+                .with_method(Some(WifiMethod::Wpa3))
+                .with_password(Some("password".to_string())),
+            Wifi::new("Office".to_string())
+                .with_method(Some(WifiMethod::Wpa2Enterprise))
+                .with_eap_method(Some(crate::EapMethod::Peap))
+                .with_phase2(Some(crate::Phase2::MsChapV2))
+                .with_anonymous_identity(Some("anonymous".to_string()))
+                .with_identity(Some("user@example.com".to_string()))
+                .with_password(Some("password".to_string())),
+            Wifi::new("Office".to_string())
+                .with_method(Some(WifiMethod::Wpa2Enterprise))
+                .with_eap_method(Some(crate::EapMethod::Tls))
+                .with_identity(Some("user".to_string()))
+                .with_public_key(Some(b"public key".to_vec())),
+            Wifi::new("Debug".to_string()).with_raw_auth_type(Some("SAE".to_string())),
+            Wifi::new("Vendor".to_string())
+                .with_raw_field("O;".to_string(), "Acme; Inc.".to_string())
+                .with_raw_field("X".to_string(), "x".to_string()),
+        ];
+        for wifi in networks {
+            assert_eq!(wifi.to_string().parse::<Wifi>().unwrap(), wifi, "{wifi}");
+        }
+    }
+
+    #[test]
+    fn raw_fields() {
+        let wifi: Wifi = r"WIFI:S:Vendor;O\;:Acme\; Inc.;X:x;;".parse().unwrap();
+        assert_eq!(
+            wifi.raw_fields(),
+            [
+                ("O;".to_string(), "Acme; Inc.".to_string()),
+                ("X".to_string(), "x".to_string())
+            ]
+        );
+        assert_eq!(wifi.payload(), r"WIFI:S:Vendor;O\;:Acme\; Inc.;X:x;;");
+    }
+
+    #[test]
+    fn invalid_payloads() {
+        for payload in [
+            "S:Test;;",
+            "WIFI:T:WPA;;",
+            "WIFI:S;;",
+            "WIFI:S:Test;H:maybe;;",
+            r"WIFI:S:Test\",
+            "WIFI:S:Test;E:FOO;;",
+            "WIFI:S:Test;K:not base64!;;",
+            "WIFI:S:Test;R:zz;;",
+        ] {
+            assert!(
+                matches!(
+                    payload.parse::<Wifi>(),
+                    Err(GenerationError::InvalidPayload(_))
+                ),
+                "{payload}"
+            );
+        }
+    }
+}
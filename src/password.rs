@@ -0,0 +1,110 @@
+use crate::{GenerationError, Wifi};
+
+/// The characters a generated password is made of
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[non_exhaustive]
+pub enum Charset {
+    /// `A-Z`, `a-z` and `0-9`
+    #[default]
+    Alphanumeric,
+    /// Like [`Charset::Alphanumeric`], but without `0`, `O`, `1`, `l` and `I`,
+    /// which are easily confused when typing the password manually
+    AlphanumericNoAmbiguous,
+}
+
+impl Charset {
+    fn characters(self) -> &'static [u8] {
+        match self {
+            Charset::Alphanumeric => {
+                b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789"
+            }
+            Charset::AlphanumericNoAmbiguous => {
+                b"ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz23456789"
+            }
+        }
+    }
+}
+
+/// Generate a random password of `len` characters from `charset` using the OS CSPRNG
+///
+/// `len` has to be a valid WPA passphrase length, i.e. between 8 and 63.
+pub fn generate_password(len: usize, charset: Charset) -> Result<String, GenerationError> {
+    if !(8..=63).contains(&len) {
+        return Err(GenerationError::InvalidPasswordLength(len));
+    }
+
+    let characters = charset.characters();
+    // only accept bytes below the largest multiple of the charset length to avoid modulo bias
+    let limit = u8::MAX - u8::MAX % characters.len() as u8;
+
+    let mut password = String::with_capacity(len);
+    let mut random = [0; 64];
+
+    while password.len() < len {
+        getrandom::getrandom(&mut random)?;
+        let missing = len - password.len();
+        password.extend(
+            random
+                .iter()
+                .filter(|&&byte| byte < limit)
+                .map(|&byte| characters[byte as usize % characters.len()] as char)
+                .take(missing),
+        );
+    }
+
+    Ok(password)
+}
+
+impl Wifi {
+    /// Set a new random password, see [`generate_password`]
+    pub fn with_generated_password(
+        self,
+        len: usize,
+        charset: Charset,
+    ) -> Result<Self, GenerationError> {
+        Ok(self.with_password(Some(generate_password(len, charset)?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn length_and_charset() {
+        for len in [8, 20, 63] {
+            for charset in [Charset::Alphanumeric, Charset::AlphanumericNoAmbiguous] {
+                let password = generate_password(len, charset).unwrap();
+                assert_eq!(password.len(), len);
+                assert!(password.bytes().all(|b| charset.characters().contains(&b)));
+            }
+        }
+
+        let password = generate_password(63, Charset::AlphanumericNoAmbiguous).unwrap();
+        assert!(!password.contains(['0', 'O', '1', 'l', 'I']));
+    }
+
+    #[test]
+    fn invalid_length() {
+        for len in [0, 7, 64] {
+            assert!(matches!(
+                generate_password(len, Charset::Alphanumeric),
+                Err(GenerationError::InvalidPasswordLength(invalid)) if invalid == len
+            ));
+        }
+    }
+
+    #[test]
+    fn random() {
+        assert_ne!(
+            generate_password(32, Charset::Alphanumeric).unwrap(),
+            generate_password(32, Charset::Alphanumeric).unwrap()
+        );
+
+        let wifi = Wifi::new("Test".to_string())
+            .with_generated_password(12, Charset::Alphanumeric)
+            .unwrap();
+        assert_eq!(wifi.password().map(str::len), Some(12));
+    }
+}
@@ -0,0 +1,232 @@
+use std::path::Path;
+
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str};
+use qrcode::Color;
+
+use crate::{GenerationError, ImageFormat, Wifi};
+
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 50.0;
+const ROW_HEIGHT: f32 = 80.0;
+const THUMBNAIL_SIZE: f32 = 64.0;
+const ROWS_PER_PAGE: usize = ((PAGE_HEIGHT - 2.0 * MARGIN) / ROW_HEIGHT) as usize;
+
+/// Write an A4 PDF listing every network with a preview of its code, its SSID
+/// and the file name its image gets in `previews_dir` (see [`Wifi::file_name`]),
+/// e.g. to keep a record of a batch run.
+///
+/// The text uses a standard PDF font, characters outside of ASCII are shown as `?`.
+pub fn generate_index_pdf(
+    networks: &[Wifi],
+    previews_dir: &Path,
+    out: &Path,
+) -> Result<(), GenerationError> {
+    let mut pdf = Pdf::new();
+    let mut next_id = Ref::new(1);
+    let mut alloc = || next_id.bump();
+
+    let catalog_id = alloc();
+    let page_tree_id = alloc();
+    let font_id = alloc();
+    let font_name = Name(b"F1");
+
+    pdf.catalog(catalog_id).pages(page_tree_id);
+    pdf.type1_font(font_id).base_font(Name(b"Helvetica"));
+
+    let mut page_ids = Vec::new();
+    // an empty batch still gets a (blank) page
+    let pages: Vec<&[Wifi]> = if networks.is_empty() {
+        vec![&[]]
+    } else {
+        networks.chunks(ROWS_PER_PAGE).collect()
+    };
+
+    for page_networks in pages {
+        let page_id = alloc();
+        let content_id = alloc();
+        page_ids.push(page_id);
+
+        let mut content = Content::new();
+        let mut images = Vec::with_capacity(page_networks.len());
+
+        for (row, wifi) in page_networks.iter().enumerate() {
+            let top = PAGE_HEIGHT - MARGIN - row as f32 * ROW_HEIGHT;
+            let image_name = format!("Im{row}");
+
+            content.save_state();
+            content.transform([
+                THUMBNAIL_SIZE,
+                0.0,
+                0.0,
+                THUMBNAIL_SIZE,
+                MARGIN,
+                top - THUMBNAIL_SIZE,
+            ]);
+            content.x_object(Name(image_name.as_bytes()));
+            content.restore_state();
+
+            let file_name = previews_dir.join(wifi.file_name(None, &ImageFormat::default()));
+            content.begin_text();
+            content.set_font(font_name, 12.0);
+            content.next_line(MARGIN + THUMBNAIL_SIZE + 16.0, top - 24.0);
+            content.show(Str(pdf_text(&wifi.ssid).as_bytes()));
+            content.set_font(font_name, 9.0);
+            content.next_line(0.0, -16.0);
+            content.show(Str(pdf_text(&file_name.display().to_string()).as_bytes()));
+            content.end_text();
+
+            let image_id = alloc();
+            let (width, modules) = thumbnail(wifi)?;
+            let mut image = pdf.image_xobject(image_id, &modules);
+            image.width(width as i32);
+            image.height(width as i32);
+            image.color_space().device_gray();
+            image.bits_per_component(8);
+            image.finish();
+            images.push((image_name, image_id));
+        }
+
+        pdf.stream(content_id, &content.finish());
+
+        let mut page = pdf.page(page_id);
+        page.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT));
+        page.parent(page_tree_id);
+        page.contents(content_id);
+        let mut resources = page.resources();
+        resources.fonts().pair(font_name, font_id);
+        let mut x_objects = resources.x_objects();
+        for (name, id) in &images {
+            x_objects.pair(Name(name.as_bytes()), *id);
+        }
+    }
+
+    pdf.pages(page_tree_id)
+        .count(page_ids.len() as i32)
+        .kids(page_ids);
+
+    std::fs::write(out, pdf.finish())?;
+
+    Ok(())
+}
+
+/// An A4 page with the code as wide as the margins allow and the SSID below it,
+/// for printing a single network
+#[cfg(feature = "zip")]
+pub(crate) fn poster_pdf(wifi: &Wifi) -> Result<Vec<u8>, GenerationError> {
+    let mut pdf = Pdf::new();
+    let catalog_id = Ref::new(1);
+    let page_tree_id = Ref::new(2);
+    let page_id = Ref::new(3);
+    let content_id = Ref::new(4);
+    let font_id = Ref::new(5);
+    let image_id = Ref::new(6);
+    let font_name = Name(b"F1");
+    let image_name = Name(b"Im0");
+
+    pdf.catalog(catalog_id).pages(page_tree_id);
+    pdf.pages(page_tree_id).kids([page_id]).count(1);
+    pdf.type1_font(font_id).base_font(Name(b"Helvetica"));
+
+    let (width, modules) = thumbnail(wifi)?;
+    let mut image = pdf.image_xobject(image_id, &modules);
+    image.width(width as i32);
+    image.height(width as i32);
+    image.color_space().device_gray();
+    image.bits_per_component(8);
+    image.finish();
+
+    let size = PAGE_WIDTH - 2.0 * MARGIN;
+    let bottom = PAGE_HEIGHT - MARGIN - size;
+    let mut content = Content::new();
+    content.save_state();
+    content.transform([size, 0.0, 0.0, size, MARGIN, bottom]);
+    content.x_object(image_name);
+    content.restore_state();
+    content.begin_text();
+    content.set_font(font_name, 24.0);
+    content.next_line(MARGIN, bottom - 40.0);
+    content.show(Str(pdf_text(&wifi.ssid).as_bytes()));
+    content.end_text();
+    pdf.stream(content_id, &content.finish());
+
+    let mut page = pdf.page(page_id);
+    page.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT));
+    page.parent(page_tree_id);
+    page.contents(content_id);
+    let mut resources = page.resources();
+    resources.fonts().pair(font_name, font_id);
+    resources.x_objects().pair(image_name, image_id);
+    resources.finish();
+    page.finish();
+
+    Ok(pdf.finish())
+}
+
+/// The code with its quiet zone at one byte per module, the PDF viewer does the scaling
+fn thumbnail(wifi: &Wifi) -> Result<(usize, Vec<u8>), GenerationError> {
+    let quiet_zone = wifi.render_options.quiet_zone_width() as usize;
+    let code = wifi.qr_code()?;
+    let code_width = code.width();
+    let width = code_width + 2 * quiet_zone;
+    let colors = code.to_colors();
+
+    let mut modules = vec![255; width * width];
+    for (index, color) in colors.into_iter().enumerate() {
+        if color == Color::Dark {
+            let (x, y) = (
+                index % code_width + quiet_zone,
+                index / code_width + quiet_zone,
+            );
+            modules[y * width + x] = 0;
+        }
+    }
+
+    Ok((width, modules))
+}
+
+/// Replace the characters the standard fonts can't show with `?`
+fn pdf_text(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_ascii() && !c.is_ascii_control() {
+                c
+            } else {
+                '?'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the number of `/Type /Page` objects, not counting the `/Type /Pages` tree
+    fn page_count(pdf: &[u8]) -> usize {
+        let needle = b"/Type /Page";
+        pdf.windows(needle.len() + 1)
+            .filter(|window| window.starts_with(needle) && window[needle.len()] != b's')
+            .count()
+    }
+
+    #[test]
+    fn index_page_count() {
+        let out = std::env::temp_dir().join(format!("wifi-qr-index-{}.pdf", std::process::id()));
+        let networks: Vec<_> = (0..ROWS_PER_PAGE + 1)
+            .map(|index| Wifi::new(format!("Network {index}")))
+            .collect();
+
+        for (networks, pages) in [
+            (&networks[..0], 1),
+            (&networks[..1], 1),
+            (&networks[..ROWS_PER_PAGE], 1),
+            (&networks[..], 2),
+        ] {
+            generate_index_pdf(networks, Path::new("previews"), &out).unwrap();
+            let pdf = std::fs::read(&out).unwrap();
+            assert_eq!(page_count(&pdf), pages, "{} networks", networks.len());
+        }
+        std::fs::remove_file(&out).unwrap();
+    }
+}
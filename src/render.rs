@@ -0,0 +1,2767 @@
+use std::fmt::Debug;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+#[cfg(feature = "image-codecs")]
+use std::time::Duration;
+
+#[cfg(feature = "qoi")]
+use arqoii::types::QoiHeader;
+
+#[cfg(feature = "cli")]
+use clap::{builder::PossibleValue, ValueEnum};
+
+use image::DynamicImage;
+use image::ImageBuffer;
+use image::Luma;
+use image::LumaA;
+use image::Rgba;
+use image::RgbaImage;
+use qrcode::render::Pixel;
+use qrcode::QrCode;
+
+use crate::{DppConfig, GenerationError, RenderOptions, RenderReport, Wifi};
+
+#[derive(Clone)]
+#[non_exhaustive]
+pub enum ImageFormat {
+    #[non_exhaustive]
+    ImageFormat(image::ImageFormat),
+    #[cfg(feature = "qoi")]
+    #[non_exhaustive]
+    Qoi,
+}
+
+impl Debug for ImageFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageFormat::ImageFormat(format) => write!(f, "{format:?}"),
+            #[cfg(feature = "qoi")]
+            ImageFormat::Qoi => write!(f, "Qoi"),
+        }
+    }
+}
+
+impl Default for ImageFormat {
+    fn default() -> Self {
+        Self::ImageFormat(image::ImageFormat::Png)
+    }
+}
+
+#[cfg(feature = "cli")]
+impl ValueEnum for ImageFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            #[cfg(feature = "qoi")]
+            Self::Qoi, Self::ImageFormat(image::ImageFormat::Png),
+            #[cfg(feature = "image-codecs")]
+            Self::ImageFormat(image::ImageFormat::Jpeg),
+            #[cfg(feature = "image-codecs")]
+            Self::ImageFormat(image::ImageFormat::Bmp),
+            #[cfg(feature = "image-codecs")]
+            Self::ImageFormat(image::ImageFormat::Gif),
+            #[cfg(feature = "webp")]
+            Self::ImageFormat(image::ImageFormat::WebP),
+        ]
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        let name = format!("{self:?}").to_lowercase();
+        Some(
+            PossibleValue::new(name),
+        )
+    }
+
+    fn from_str(input: &str, _ignore_case: bool) -> Result<Self, String> {
+        Self::from_name(input).ok_or_else(|| format!("unknown image format `{input}`"))
+    }
+}
+
+impl ImageFormat {
+    pub fn png() -> Self {
+        Self::ImageFormat(image::ImageFormat::Png)
+    }
+
+    #[cfg(feature = "qoi")]
+    pub fn qoi() -> Self {
+        Self::Qoi
+    }
+
+    /// Lossless WebP, encoded with libwebp
+    #[cfg(feature = "webp")]
+    pub fn webp() -> Self {
+        Self::ImageFormat(image::ImageFormat::WebP)
+    }
+
+    /// Look up a format by name (e.g. `png`, `qoi` or `jpeg`), ignoring case
+    ///
+    /// Only the formats that are available with the enabled features are recognized.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "png" => Some(Self::png()),
+            #[cfg(feature = "qoi")]
+            "qoi" => Some(Self::Qoi),
+            #[cfg(feature = "image-codecs")]
+            "jpeg" | "jpg" => Some(Self::ImageFormat(image::ImageFormat::Jpeg)),
+            #[cfg(feature = "image-codecs")]
+            "bmp" => Some(Self::ImageFormat(image::ImageFormat::Bmp)),
+            #[cfg(feature = "image-codecs")]
+            "gif" => Some(Self::ImageFormat(image::ImageFormat::Gif)),
+            #[cfg(feature = "webp")]
+            "webp" => Some(Self::webp()),
+            _ => None,
+        }
+    }
+
+    /// Look up a format by file extension (e.g. `png`, `jpg` or `qoi`), ignoring case
+    ///
+    /// The names of [`ImageFormat::from_name`] double as extensions.
+    /// SVG isn't an [`ImageFormat`], it's written by `Wifi::generate_svg_file` instead.
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        Self::from_name(ext)
+    }
+
+    /// The MIME type of the format, e.g. for a `Content-Type` header
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ImageFormat::ImageFormat(format) => format.to_mime_type(),
+            #[cfg(feature = "qoi")]
+            ImageFormat::Qoi => "image/qoi",
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::ImageFormat(format) => format.extensions_str()[0],
+            #[cfg(feature = "qoi")]
+            ImageFormat::Qoi => "qoi",
+        }
+    }
+}
+
+/// Everything needed to answer an HTTP request with the code of a network,
+/// see [`Wifi::render_web`]
+#[derive(Debug, Clone)]
+pub struct WebRender {
+    /// the encoded image
+    pub bytes: Vec<u8>,
+    pub content_type: &'static str,
+    /// the suggested file name, without characters that aren't allowed in file names
+    /// (see [`Wifi::file_name`])
+    pub filename: String,
+    pub report: RenderReport,
+}
+
+/// Extra part appended to generated file names,
+/// so concurrent runs writing into the same directory don't overwrite each other's files
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub enum FileNameSuffix {
+    #[default]
+    None,
+    /// The [`Wifi::fingerprint`] of the network,
+    /// only distinguishes networks that differ in more than the SSID
+    Fingerprint,
+    /// The next value of a counter, share it between all runs writing into the same directory
+    Counter(Arc<AtomicUsize>),
+}
+
+/// Counters are only equal if they are the same shared counter
+impl PartialEq for FileNameSuffix {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Counter(a), Self::Counter(b)) => Arc::ptr_eq(a, b),
+            _ => core::mem::discriminant(self) == core::mem::discriminant(other),
+        }
+    }
+}
+
+/// Compression settings for PNG output, see [`Wifi::with_png_options`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PngOptions {
+    pub compression: PngCompression,
+    pub filter: PngFilter,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PngCompression {
+    #[default]
+    Default,
+    /// faster encoding, larger files
+    Fast,
+    /// smallest files, slower encoding
+    Best,
+}
+
+/// The filter applied to each row before compression
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PngFilter {
+    NoFilter,
+    Sub,
+    Up,
+    Avg,
+    Paeth,
+    /// choose the best filter for each row
+    #[default]
+    Adaptive,
+}
+
+impl PngCompression {
+    #[cfg(feature = "image-codecs")]
+    fn image_compression(self) -> image::codecs::png::CompressionType {
+        match self {
+            PngCompression::Default => image::codecs::png::CompressionType::Default,
+            PngCompression::Fast => image::codecs::png::CompressionType::Fast,
+            PngCompression::Best => image::codecs::png::CompressionType::Best,
+        }
+    }
+
+    #[cfg(feature = "png-only")]
+    fn png_compression(self) -> png::Compression {
+        match self {
+            PngCompression::Default => png::Compression::Default,
+            PngCompression::Fast => png::Compression::Fast,
+            PngCompression::Best => png::Compression::Best,
+        }
+    }
+}
+
+impl PngFilter {
+    #[cfg(feature = "image-codecs")]
+    fn image_filter(self) -> image::codecs::png::FilterType {
+        match self {
+            PngFilter::NoFilter => image::codecs::png::FilterType::NoFilter,
+            PngFilter::Sub => image::codecs::png::FilterType::Sub,
+            PngFilter::Up => image::codecs::png::FilterType::Up,
+            PngFilter::Avg => image::codecs::png::FilterType::Avg,
+            PngFilter::Paeth => image::codecs::png::FilterType::Paeth,
+            PngFilter::Adaptive => image::codecs::png::FilterType::Adaptive,
+        }
+    }
+
+    /// `None` for [`PngFilter::Adaptive`], which the `png` crate sets separately
+    #[cfg(feature = "png-only")]
+    fn png_filter(self) -> Option<png::FilterType> {
+        match self {
+            PngFilter::NoFilter => Some(png::FilterType::NoFilter),
+            PngFilter::Sub => Some(png::FilterType::Sub),
+            PngFilter::Up => Some(png::FilterType::Up),
+            PngFilter::Avg => Some(png::FilterType::Avg),
+            PngFilter::Paeth => Some(png::FilterType::Paeth),
+            PngFilter::Adaptive => None,
+        }
+    }
+}
+
+/// Quality settings for JPEG output, see [`Wifi::with_jpeg_options`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JpegOptions {
+    /// from 1 (smallest) to 100 (best), 75 by default
+    pub quality: u8,
+}
+
+impl Default for JpegOptions {
+    fn default() -> Self {
+        Self { quality: 75 }
+    }
+}
+
+/// The pixel types a code is rendered in, see [`Rendered`]
+trait CodePixel: image::Pixel<Subpixel = u8> + Debug + 'static {
+    /// `color` is gray for the grayscale types, which only keep its red channel
+    fn from_color(color: [u8; 4]) -> Self;
+
+    /// straight RGBA
+    fn color(self) -> [u8; 4];
+
+    fn into_dynamic(buffer: ImageBuffer<Self, Vec<u8>>) -> DynamicImage;
+}
+
+impl CodePixel for Luma<u8> {
+    fn from_color([luma, ..]: [u8; 4]) -> Self {
+        Luma([luma])
+    }
+
+    fn color(self) -> [u8; 4] {
+        let Luma([luma]) = self;
+        [luma, luma, luma, 255]
+    }
+
+    fn into_dynamic(buffer: ImageBuffer<Self, Vec<u8>>) -> DynamicImage {
+        DynamicImage::ImageLuma8(buffer)
+    }
+}
+
+impl CodePixel for LumaA<u8> {
+    fn from_color([luma, _, _, alpha]: [u8; 4]) -> Self {
+        LumaA([luma, alpha])
+    }
+
+    fn color(self) -> [u8; 4] {
+        let LumaA([luma, alpha]) = self;
+        [luma, luma, luma, alpha]
+    }
+
+    fn into_dynamic(buffer: ImageBuffer<Self, Vec<u8>>) -> DynamicImage {
+        DynamicImage::ImageLumaA8(buffer)
+    }
+}
+
+impl CodePixel for Rgba<u8> {
+    fn from_color(color: [u8; 4]) -> Self {
+        Rgba(color)
+    }
+
+    fn color(self) -> [u8; 4] {
+        self.0
+    }
+
+    fn into_dynamic(buffer: ImageBuffer<Self, Vec<u8>>) -> DynamicImage {
+        DynamicImage::ImageRgba8(buffer)
+    }
+}
+
+/// A rendered code in the smallest pixel type that holds its colors
+enum Rendered {
+    /// black on white
+    Gray(Image<Luma<u8>>),
+    /// black on a transparent background
+    GrayAlpha(Image<LumaA<u8>>),
+    /// any other colors, or with a logo
+    Color(Image<Rgba<u8>>),
+}
+
+impl Rendered {
+    fn save_as(
+        &self,
+        format: Option<ImageFormat>,
+        file_path: &Path,
+    ) -> Result<ImageFormat, GenerationError> {
+        match self {
+            Rendered::Gray(image) => image.save_as(format, file_path),
+            Rendered::GrayAlpha(image) => image.save_as(format, file_path),
+            Rendered::Color(image) => image.save_as(format, file_path),
+        }
+    }
+
+    fn save_to_writer<W: std::io::Write>(
+        &self,
+        format: ImageFormat,
+        writer: &mut W,
+    ) -> Result<(), GenerationError> {
+        match self {
+            Rendered::Gray(image) => image.save_to_writer(format, writer),
+            Rendered::GrayAlpha(image) => image.save_to_writer(format, writer),
+            Rendered::Color(image) => image.save_to_writer(format, writer),
+        }
+    }
+
+    fn to_bytes(&self, format: ImageFormat) -> Result<Vec<u8>, GenerationError> {
+        match self {
+            Rendered::Gray(image) => image.to_bytes(format),
+            Rendered::GrayAlpha(image) => image.to_bytes(format),
+            Rendered::Color(image) => image.to_bytes(format),
+        }
+    }
+
+    #[cfg(feature = "verify")]
+    fn verify(&self, payload: &str, mirrored: bool) -> Result<(), GenerationError> {
+        match self {
+            Rendered::Gray(image) => image.verify(payload, mirrored),
+            Rendered::GrayAlpha(image) => image.verify(payload, mirrored),
+            Rendered::Color(image) => image.verify(payload, mirrored),
+        }
+    }
+
+    fn into_rgba(self) -> Image<Rgba<u8>> {
+        match self {
+            Rendered::Gray(image) => image.into_rgba(),
+            Rendered::GrayAlpha(image) => image.into_rgba(),
+            Rendered::Color(image) => image,
+        }
+    }
+
+    fn dimensions(&self) -> (u32, u32) {
+        match self {
+            Rendered::Gray(image) => image.buffer.dimensions(),
+            Rendered::GrayAlpha(image) => image.buffer.dimensions(),
+            Rendered::Color(image) => image.buffer.dimensions(),
+        }
+    }
+
+    fn to_dynamic(&self) -> DynamicImage {
+        match self {
+            Rendered::Gray(image) => image.to_dynamic(),
+            Rendered::GrayAlpha(image) => image.to_dynamic(),
+            Rendered::Color(image) => image.to_dynamic(),
+        }
+    }
+}
+
+/// An image rendered from one or more codes, e.g. by [`generate_grid`],
+/// which is encoded with the PNG and JPEG options of the first network
+pub struct RenderedImage(Rendered);
+
+impl RenderedImage {
+    /// width and height in pixels
+    pub fn dimensions(&self) -> (u32, u32) {
+        self.0.dimensions()
+    }
+
+    /// Write the image like [`Wifi::generate_image_file`]
+    pub fn save(
+        &self,
+        format: Option<ImageFormat>,
+        file_path: &Path,
+    ) -> Result<ImageFormat, GenerationError> {
+        self.0.save_as(format, file_path)
+    }
+
+    /// Encode the image in memory like [`Wifi::generate_image_bytes`]
+    pub fn to_bytes(&self, format: ImageFormat) -> Result<Vec<u8>, GenerationError> {
+        self.0.to_bytes(format)
+    }
+
+    /// The pixels, e.g. to draw the image into another one
+    pub fn to_dynamic(&self) -> DynamicImage {
+        self.0.to_dynamic()
+    }
+}
+
+struct Image<P: CodePixel> {
+    buffer: ImageBuffer<P, Vec<u8>>,
+    /// physical resolution, written to the PNG pHYs chunk
+    #[cfg(feature = "print")]
+    dpi: Option<u32>,
+    png_options: Option<PngOptions>,
+    jpeg_options: Option<JpegOptions>,
+}
+
+impl<P: CodePixel> Image<P> {
+    fn new(buffer: ImageBuffer<P, Vec<u8>>) -> Self {
+        Self {
+            buffer,
+            #[cfg(feature = "print")]
+            dpi: None,
+            png_options: None,
+            jpeg_options: None,
+        }
+    }
+
+    /// the buffer as is, without an alpha channel if every pixel is opaque
+    fn to_dynamic(&self) -> DynamicImage {
+        let image = P::into_dynamic(self.buffer.clone());
+        if self.has_alpha() {
+            return image;
+        }
+        match image {
+            DynamicImage::ImageLumaA8(_) => DynamicImage::ImageLuma8(image.into_luma8()),
+            DynamicImage::ImageRgba8(_) => DynamicImage::ImageRgb8(image.into_rgb8()),
+            image => image,
+        }
+    }
+
+    /// [`Image::to_dynamic`], but with the colors blended onto white for JPEG,
+    /// which would otherwise drop the alpha channel and show the color underneath,
+    /// and always RGBA for GIF, which has no grayscale encoding
+    fn to_dynamic_for(&self, format: image::ImageFormat) -> DynamicImage {
+        if format == image::ImageFormat::Jpeg && self.has_alpha() {
+            self.flattened().to_dynamic()
+        } else if format == image::ImageFormat::Gif {
+            DynamicImage::ImageRgba8(self.rgba())
+        } else {
+            self.to_dynamic()
+        }
+    }
+
+    fn flattened(&self) -> Image<P> {
+        let over_white = |[r, g, b, a]: [u8; 4]| {
+            let blend =
+                |c: u8| ((u16::from(c) * u16::from(a) + 255 * (255 - u16::from(a))) / 255) as u8;
+            [blend(r), blend(g), blend(b), 255]
+        };
+        Image {
+            buffer: ImageBuffer::from_fn(self.buffer.width(), self.buffer.height(), |x, y| {
+                P::from_color(over_white(self.pixel(x, y)))
+            }),
+            #[cfg(feature = "print")]
+            dpi: self.dpi,
+            png_options: self.png_options,
+            jpeg_options: self.jpeg_options,
+        }
+    }
+
+    fn has_alpha(&self) -> bool {
+        self.buffer.pixels().any(|pixel| pixel.color()[3] != 255)
+    }
+
+    fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        self.buffer.get_pixel(x, y).color()
+    }
+
+    /// decode the image as a scanner would see it, i.e. over white, and compare with `payload`
+    #[cfg(feature = "verify")]
+    fn verify(&self, payload: &str, mirrored: bool) -> Result<(), GenerationError> {
+        let flattened = self.flattened();
+        let (width, height) = self.buffer.dimensions();
+        let mut prepared =
+            rqrr::PreparedImage::prepare_from_greyscale(width as usize, height as usize, |x, y| {
+                // scanners can't read a mirrored code, undo it like whatever displays it would
+                let x = if mirrored { width as usize - 1 - x } else { x };
+                let [r, g, b, _] = flattened.pixel(x as u32, y as u32);
+                ((299 * u32::from(r) + 587 * u32::from(g) + 114 * u32::from(b)) / 1000) as u8
+            });
+
+        // the decoded text is never part of the error, it contains the password
+        let grid = prepared.detect_grids().into_iter().next().ok_or_else(|| {
+            GenerationError::VerificationFailed("no code found in the image".to_string())
+        })?;
+        let (_, decoded) = grid.decode().map_err(|err| {
+            GenerationError::VerificationFailed(format!("decoding failed: {err}"))
+        })?;
+        if decoded != payload {
+            return Err(GenerationError::VerificationFailed(
+                "the decoded text differs".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn rgba(&self) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+        ImageBuffer::from_fn(self.buffer.width(), self.buffer.height(), |x, y| {
+            Rgba(self.pixel(x, y))
+        })
+    }
+
+    fn into_rgba(self) -> Image<Rgba<u8>> {
+        Image {
+            buffer: self.rgba(),
+            #[cfg(feature = "print")]
+            dpi: self.dpi,
+            png_options: self.png_options,
+            jpeg_options: self.jpeg_options,
+        }
+    }
+
+    pub fn save(&self, format: ImageFormat, file_path: &Path) -> Result<(), GenerationError> {
+        match format {
+            #[cfg(feature = "png-only")]
+            ImageFormat::ImageFormat(image::ImageFormat::Png) => {
+                self.write_png(std::io::BufWriter::new(std::fs::File::create(file_path)?))?;
+            }
+            #[cfg(feature = "webp")]
+            ImageFormat::ImageFormat(image::ImageFormat::WebP) => {
+                std::fs::write(file_path, self.to_webp()?)?;
+            }
+            ImageFormat::ImageFormat(format) if self.has_encoder_options(format) => {
+                self.write_image(
+                    format,
+                    &mut std::io::BufWriter::new(std::fs::File::create(file_path)?),
+                )?;
+            }
+            ImageFormat::ImageFormat(format) => {
+                self.to_dynamic_for(format)
+                    .save_with_format(file_path, format)?;
+            }
+            #[cfg(feature = "qoi")]
+            ImageFormat::Qoi => {
+                self.save_to_writer(
+                    format,
+                    &mut std::io::BufWriter::new(std::fs::File::create(file_path)?),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Encode into any sink, PNG (with `png-only`) and QOI are streamed,
+    /// other formats need a seekable writer and are encoded into a buffer first
+    pub fn save_to_writer<W: std::io::Write>(
+        &self,
+        format: ImageFormat,
+        writer: &mut W,
+    ) -> Result<(), GenerationError> {
+        match format {
+            #[cfg(feature = "png-only")]
+            ImageFormat::ImageFormat(image::ImageFormat::Png) => self.write_png(&mut *writer)?,
+            ImageFormat::ImageFormat(_) => writer.write_all(&self.to_bytes(format)?)?,
+            #[cfg(feature = "qoi")]
+            ImageFormat::Qoi => {
+                let mut buf = [0; 4096];
+                let mut encoder = self.qoi_encoder();
+                loop {
+                    let len = buf
+                        .iter_mut()
+                        .zip(&mut encoder)
+                        .map(|(slot, byte)| *slot = byte)
+                        .count();
+                    if len == 0 {
+                        break;
+                    }
+                    writer.write_all(&buf[..len])?;
+                }
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Encode the image in memory, e.g. for bundling it into an archive
+    fn to_bytes(&self, format: ImageFormat) -> Result<Vec<u8>, GenerationError> {
+        let mut data = std::io::Cursor::new(Vec::new());
+        match format {
+            #[cfg(feature = "png-only")]
+            ImageFormat::ImageFormat(image::ImageFormat::Png) => self.write_png(&mut data)?,
+            #[cfg(feature = "webp")]
+            ImageFormat::ImageFormat(image::ImageFormat::WebP) => return self.to_webp(),
+            ImageFormat::ImageFormat(format) => self.write_image(format, &mut data)?,
+            #[cfg(feature = "qoi")]
+            ImageFormat::Qoi => data.get_mut().extend(self.qoi_encoder()),
+        }
+        Ok(data.into_inner())
+    }
+
+    /// Encode with the `image` crate, using the [`PngOptions`] and [`JpegOptions`] if set
+    fn write_image<W: std::io::Write + std::io::Seek>(
+        &self,
+        format: image::ImageFormat,
+        writer: &mut W,
+    ) -> Result<(), GenerationError> {
+        let image = self.to_dynamic_for(format);
+        match (format, self.png_options, self.jpeg_options) {
+            #[cfg(feature = "image-codecs")]
+            (image::ImageFormat::Png, Some(options), _) => {
+                image.write_with_encoder(image::codecs::png::PngEncoder::new_with_quality(
+                    writer,
+                    options.compression.image_compression(),
+                    options.filter.image_filter(),
+                ))?
+            }
+            #[cfg(feature = "image-codecs")]
+            (image::ImageFormat::Jpeg, _, Some(JpegOptions { quality })) => {
+                image.write_to(writer, image::ImageOutputFormat::Jpeg(quality.clamp(1, 100)))?
+            }
+            (format, ..) => image.write_to(writer, format)?,
+        }
+        Ok(())
+    }
+
+    fn has_encoder_options(&self, format: image::ImageFormat) -> bool {
+        match format {
+            image::ImageFormat::Png => self.png_options.is_some(),
+            image::ImageFormat::Jpeg => self.jpeg_options.is_some(),
+            _ => false,
+        }
+    }
+
+    #[cfg(feature = "qoi")]
+    fn qoi_encoder(&self) -> impl Iterator<Item = u8> + '_ {
+        // QOI always encodes RGBA, the channel count in the header is informational only.
+        // The encoder starts from an opaque previous pixel and only emits alpha when it changes,
+        // so with every pixel at alpha 255 an `Rgb` file decodes to exactly the gray pixels.
+        // There is no grayscale mode, but equal channels don't cost extra either:
+        // a two color code is all runs and index hits, one byte each, whatever the channel count.
+        arqoii::QoiEncoder::new(
+            QoiHeader::new(
+                self.buffer.width(),
+                self.buffer.height(),
+                if self.has_alpha() {
+                    arqoii::types::QoiChannels::Rgba
+                } else {
+                    arqoii::types::QoiChannels::Rgb
+                },
+                arqoii::types::QoiColorSpace::SRgbWithLinearAlpha,
+            ),
+            self.buffer.pixels().map(|pixel| {
+                let [r, g, b, a] = pixel.color();
+                arqoii::Pixel { r, g, b, a }
+            }),
+        )
+    }
+    /// Save in the format guessed from the extension of `file_path` and return it
+    pub fn save_guess_format(&self, file_path: &Path) -> Result<ImageFormat, GenerationError> {
+        let format = guess_format(file_path)?;
+        self.save(format.clone(), file_path)?;
+        Ok(format)
+    }
+
+    /// Save in `format`, or in the one guessed from the extension if `None`, and return it
+    fn save_as(
+        &self,
+        format: Option<ImageFormat>,
+        file_path: &Path,
+    ) -> Result<ImageFormat, GenerationError> {
+        match format {
+            Some(format) => {
+                self.save(format.clone(), file_path)?;
+                Ok(format)
+            }
+            None => self.save_guess_format(file_path),
+        }
+    }
+
+    /// The image crate can only encode WebP with an older libwebp binding, so use it directly
+    #[cfg(feature = "webp")]
+    fn to_webp(&self) -> Result<Vec<u8>, GenerationError> {
+        let (width, height) = self.buffer.dimensions();
+        let webp = if self.has_alpha() {
+            let rgba = self.to_dynamic().into_rgba8();
+            webp::Encoder::from_rgba(&rgba, width, height).encode_simple(true, 75.0)
+        } else {
+            let rgb = self.to_dynamic().into_rgb8();
+            webp::Encoder::from_rgb(&rgb, width, height).encode_simple(true, 75.0)
+        };
+        Ok(webp.map_err(GenerationError::WebPError)?.to_vec())
+    }
+
+    /// Encode the buffer as a PNG using the `png` crate directly,
+    /// so PNG output keeps working without the codecs of the `image` crate
+    #[cfg(feature = "png-only")]
+    fn write_png<W: std::io::Write>(&self, writer: W) -> Result<(), GenerationError> {
+        let mut encoder = png::Encoder::new(writer, self.buffer.width(), self.buffer.height());
+        encoder.set_depth(png::BitDepth::Eight);
+        #[cfg(feature = "print")]
+        if let Some(dpi) = self.dpi {
+            let pixels_per_meter = (f64::from(dpi) / MM_PER_INCH * 1000.0).round() as u32;
+            encoder.set_pixel_dims(Some(png::PixelDimensions {
+                xppu: pixels_per_meter,
+                yppu: pixels_per_meter,
+                unit: png::Unit::Meter,
+            }));
+        }
+        if let Some(PngOptions { compression, filter }) = self.png_options {
+            encoder.set_compression(compression.png_compression());
+            match filter.png_filter() {
+                Some(filter) => encoder.set_filter(filter),
+                None => encoder.set_adaptive_filter(png::AdaptiveFilterType::Adaptive),
+            }
+        }
+        let image = self.to_dynamic();
+        encoder.set_color(match image.color() {
+            image::ColorType::L8 => png::ColorType::Grayscale,
+            image::ColorType::La8 => png::ColorType::GrayscaleAlpha,
+            image::ColorType::Rgb8 => png::ColorType::Rgb,
+            _ => png::ColorType::Rgba,
+        });
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(image.as_bytes())?;
+        Ok(())
+    }
+}
+
+
+#[derive(Debug, Clone, Copy)]
+struct Px<P>(P);
+
+struct Canvas<P: CodePixel>(Px<P>, Image<P>);
+
+impl<P: CodePixel> Pixel for Px<P> {
+    type Image = Image<P>;
+
+    type Canvas = Canvas<P>;
+
+    fn default_color(color: qrcode::Color) -> Self {
+        Self(P::from_color(color.select([0, 0, 0, 255], [255; 4])))
+    }
+}
+
+impl<P: CodePixel> qrcode::render::Canvas for Canvas<P> {
+    type Pixel = Px<P>;
+
+    type Image = <Px<P> as Pixel>::Image;
+
+    fn new(width: u32, height: u32, dark_pixel: Self::Pixel, light_pixel: Self::Pixel) -> Self {
+        Self(dark_pixel, Image::new(ImageBuffer::from_pixel(width, height, light_pixel.0)))
+    }
+
+    fn draw_dark_pixel(&mut self, x: u32, y: u32) {
+        self.1.buffer.put_pixel(x, y, self.0.0)
+    }
+
+    fn into_image(self) -> Self::Image {
+        self.1
+    }
+}
+
+impl Wifi {
+    /// Mirror the rendered image horizontally, for rear-projection setups
+    /// where the screen flips the image back.
+    ///
+    /// QR scanners generally can't read a mirrored code,
+    /// so only use this when something else undoes the mirroring.
+    pub fn with_mirror(mut self, mirror: bool) -> Self {
+        self.render_options.mirror = mirror;
+        self
+    }
+
+    /// Draw `logo` centered over the code, e.g. for branded codes
+    ///
+    /// The logo keeps its aspect ratio and is scaled to fit into `scale` of the code width,
+    /// at most 20% so the code still decodes.
+    /// The code is rendered at [`EcLevel::H`] unless a level is set with [`Wifi::with_ec_level`].
+    ///
+    /// [`EcLevel::H`]: qrcode::EcLevel::H
+    pub fn with_logo(mut self, logo: DynamicImage, scale: f32) -> Self {
+        self.render_options.logo = Some((logo, scale));
+        self
+    }
+
+    /// Clear a border of `modules` light modules around the logo, so it doesn't touch
+    /// the modules around it, see [`Wifi::with_logo`]
+    ///
+    /// The border hides modules on top of the ones under the logo, so keep it to one or two,
+    /// the code stays at [`EcLevel::H`] unless another level is set.
+    ///
+    /// [`EcLevel::H`]: qrcode::EcLevel::H
+    pub fn with_logo_quiet_zone(mut self, modules: u32) -> Self {
+        self.render_options.logo_quiet_zone = modules;
+        self
+    }
+
+    /// Tile `tile` across the quiet zone, e.g. a faint logo watermark for branded codes
+    ///
+    /// The tile is drawn over the light background, but not within the 4 modules around the code
+    /// scanners need to stay clear, so it only shows with a wider border (see [`Wifi::with_border`])
+    /// or a print size larger than the code.
+    pub fn with_quiet_zone_pattern(mut self, tile: DynamicImage) -> Self {
+        self.render_options.quiet_zone_pattern = Some(tile);
+        self
+    }
+
+    /// Print `caption` (e.g. the SSID) centered below the code, the image grows by the height of the text
+    ///
+    /// Captions that are too wide are drawn smaller and then cut off,
+    /// characters outside of printable ASCII are drawn as `?`.
+    /// The code is rendered at [`EcLevel::Q`] unless a level is set with [`Wifi::with_ec_level`].
+    ///
+    /// [`EcLevel::Q`]: qrcode::EcLevel::Q
+    pub fn with_caption(mut self, caption: Option<String>) -> Self {
+        self.render_options.caption = caption;
+        self
+    }
+
+    /// Draw corner brackets, like a camera focus reticle, in the quiet zone around the code
+    ///
+    /// The brackets stay clear of the modules, so they don't affect scanning.
+    /// They are left out if the quiet zone is narrower than 2 modules.
+    pub fn with_scan_guide(mut self, scan_guide: bool) -> Self {
+        self.render_options.scan_guide = scan_guide;
+        self
+    }
+
+    /// Round the outer corners of the three finder patterns, the data modules stay square
+    pub fn with_rounded_finders(mut self, rounded_finders: bool) -> Self {
+        self.render_options.rounded_finders = rounded_finders;
+        self
+    }
+
+    /// Compress PNG output with these settings instead of the encoder's defaults,
+    /// e.g. [`PngCompression::Best`] for smaller files on the web
+    pub fn with_png_options(mut self, options: Option<PngOptions>) -> Self {
+        self.render_options.png_options = options;
+        self
+    }
+
+    /// Encode JPEG output with these settings instead of the encoder's defaults,
+    /// needs the `image-codecs` feature like JPEG output itself
+    pub fn with_jpeg_options(mut self, options: Option<JpegOptions>) -> Self {
+        self.render_options.jpeg_options = options;
+        self
+    }
+
+    /// Make the light modules and the quiet zone fully transparent,
+    /// see [`RenderOptions::transparent_background`]
+    pub fn with_luma_alpha_transparency(mut self, transparent: bool) -> Self {
+        self.render_options.transparent_background = transparent;
+        self
+    }
+
+    /// Render the image so it is `width_mm` wide (including the quiet zone) when printed at `dpi`
+    ///
+    /// The modules are scaled to a whole number of pixels and the remainder is added to the
+    /// quiet zone, so the image has exactly the requested size.
+    /// The resolution is stored in PNG output, other formats only get the pixel size right.
+    /// Rendering fails with [`GenerationError::ModuleTooSmall`] if the modules would come out
+    /// smaller than what phone cameras can reliably scan.
+    /// Takes precedence over [`Wifi::with_module_size`] and [`Wifi::with_min_dimensions`].
+    #[cfg(feature = "print")]
+    pub fn for_print(mut self, width_mm: f64, dpi: u32) -> Self {
+        self.render_options.print_size = Some((width_mm, dpi));
+        self
+    }
+
+    /// Render the image and write it to `file_path` in `format`,
+    /// or in the format guessed from the extension if `None`
+    ///
+    /// Returns the format the image was written in.
+    pub fn generate_image_file(
+        &self,
+        format: Option<ImageFormat>,
+        file_path: &Path,
+    ) -> Result<ImageFormat, GenerationError> {
+        self.render()?.save_as(format, file_path)
+    }
+
+    /// Like [`Wifi::generate_image_file`], but fails with [`GenerationError::ValidationError`]
+    /// instead of generating a code devices would reject, see [`Wifi::validate`]
+    pub fn generate_image_file_validated(
+        &self,
+        format: Option<ImageFormat>,
+        file_path: &Path,
+    ) -> Result<ImageFormat, GenerationError> {
+        self.validate()?;
+        self.generate_image_file(format, file_path)
+    }
+
+    /// Like [`Wifi::generate_image_file`], but decodes the rendered image before writing it
+    /// and fails with [`GenerationError::VerificationFailed`] unless it reads back as [`Wifi::payload`],
+    /// e.g. to catch colors with too little contrast
+    #[cfg(feature = "verify")]
+    pub fn generate_image_file_verified(
+        &self,
+        format: Option<ImageFormat>,
+        file_path: &Path,
+    ) -> Result<ImageFormat, GenerationError> {
+        let image = self.render()?;
+        image.verify(&self.payload(), self.render_options.mirror)?;
+        image.save_as(format, file_path)
+    }
+
+    /// Like [`Wifi::generate_image_file`], but renders and encodes on a blocking thread
+    /// and writes the file with `tokio::fs`, so it doesn't stall the async runtime
+    ///
+    /// Needs to be called within a Tokio runtime.
+    #[cfg(feature = "tokio")]
+    pub async fn generate_image_file_async(
+        &self,
+        format: Option<ImageFormat>,
+        file_path: &Path,
+    ) -> Result<ImageFormat, GenerationError> {
+        let format = match format {
+            Some(format) => format,
+            None => guess_format(file_path)?,
+        };
+
+        let wifi = self.clone();
+        let encode_format = format.clone();
+        let bytes = tokio::task::spawn_blocking(move || wifi.generate_image_bytes(encode_format))
+            .await
+            .map_err(std::io::Error::other)??;
+
+        tokio::fs::write(file_path, bytes).await?;
+        Ok(format)
+    }
+
+    /// Render the image and encode it in memory instead of writing it to a file
+    pub fn generate_image_bytes(&self, format: ImageFormat) -> Result<Vec<u8>, GenerationError> {
+        self.render()?.to_bytes(format)
+    }
+
+    /// Render the image and encode it into `writer`, see [`Wifi::generate_image_bytes`]
+    /// for a buffer holding the whole image
+    pub fn generate_image_to_writer<W: std::io::Write>(
+        &self,
+        format: ImageFormat,
+        writer: &mut W,
+    ) -> Result<(), GenerationError> {
+        self.render()?.save_to_writer(format, writer)
+    }
+
+    /// Render the image without encoding it, e.g. to hand the pixels to a clipboard
+    pub fn generate_rgba_image(&self) -> Result<image::RgbaImage, GenerationError> {
+        Ok(self.render()?.into_rgba().buffer)
+    }
+
+    /// Render and encode the image in memory,
+    /// together with its content type, a suggested file name and the [`RenderReport`]
+    pub fn render_web(&self, format: ImageFormat) -> Result<WebRender, GenerationError> {
+        Ok(WebRender {
+            bytes: self.generate_image_bytes(format.clone())?,
+            content_type: format.content_type(),
+            filename: self.file_name(None, &format),
+            report: self.render_report()?,
+        })
+    }
+
+    /// Render the image as a `data:` URI, e.g. for the `src` of an HTML `<img>`
+    pub fn generate_data_uri(&self, format: ImageFormat) -> Result<String, GenerationError> {
+        use base64::Engine;
+
+        let bytes = self.generate_image_bytes(format.clone())?;
+        let data = base64::engine::general_purpose::STANDARD.encode(bytes);
+        Ok(format!("data:{};base64,{data}", format.content_type()))
+    }
+
+    /// Generate the codes for a network that uses separate SSIDs for the 2.4GHz and 5GHz band,
+    /// all other settings are shared.
+    /// Each code is captioned with its band, replacing the caption of this network.
+    ///
+    /// The images are written to `dir` as `wifi-<ssid>-2.4GHz.<ext>` and `wifi-<ssid>-5GHz.<ext>`
+    /// (see [`Wifi::file_name`]),
+    /// the returned paths are in that order.
+    pub fn generate_band_pair(
+        &self,
+        ssid_24: &str,
+        ssid_5: &str,
+        dir: &Path,
+        format: ImageFormat,
+    ) -> Result<[PathBuf; 2], GenerationError> {
+        let generate = |band: &str, ssid: &str| {
+            let wifi = Wifi {
+                ssid: ssid.to_string(),
+                ..self.clone()
+            }
+            .with_caption(Some(band.to_string()));
+            let path = dir.join(wifi.file_name(Some(band), &format));
+            wifi.generate_image_file(Some(format.clone()), &path)?;
+            Ok::<_, GenerationError>(path)
+        };
+
+        Ok([generate("2.4GHz", ssid_24)?, generate("5GHz", ssid_5)?])
+    }
+
+    /// Render a `width` by `height` banner, e.g. for a wide digital sign, with the code filling
+    /// the height on the left and the SSID (and with `show_password` the password) on the right
+    ///
+    /// The text is drawn as large as the space next to the code allows, in the colors of the code.
+    /// The banner has to be wider than high and high enough for one pixel per module.
+    pub fn generate_banner(
+        &self,
+        width: u32,
+        height: u32,
+        show_password: bool,
+        format: Option<ImageFormat>,
+        file_path: &Path,
+    ) -> Result<ImageFormat, GenerationError> {
+        let mut lines = vec![self.ssid.as_str()];
+        if let Some(password) = self.password.as_deref().filter(|_| show_password) {
+            lines.push(password);
+        }
+        // the band of each line is 14 font pixels high, including its padding
+        let text_height = 14 * lines.len() as u32;
+
+        let code = self.qr_code()?;
+        let modules = code.width() as u32 + 2 * self.render_options.quiet_zone_width();
+        let module_size = height / modules;
+        if module_size == 0 || width <= height || height < text_height {
+            return Err(GenerationError::BannerTooSmall { width, height });
+        }
+
+        let wifi = self.clone().with_render_options(RenderOptions {
+            module_size: Some(module_size),
+            min_dimensions: None,
+            caption: None,
+            #[cfg(feature = "print")]
+            print_size: None,
+            ..self.render_options.clone()
+        });
+        let rendered = wifi.render_code(&code)?.into_rgba();
+        let (dark, light) = self.pixel_colors();
+        let mut banner = Image::new(ImageBuffer::from_pixel(width, height, Rgba(light)));
+        banner.png_options = self.render_options.png_options;
+        banner.jpeg_options = self.render_options.jpeg_options;
+
+        // centered in the square on the left
+        let offset = (height - rendered.buffer.height()) / 2;
+        image::imageops::replace(
+            &mut banner.buffer,
+            &rendered.buffer,
+            offset.into(),
+            offset.into(),
+        );
+
+        let text_width = width - height;
+        let max_scale = height / text_height;
+        let bands: Vec<_> = lines
+            .into_iter()
+            .map(|line| crate::caption::text_band(line, text_width, max_scale))
+            .collect();
+        let mut top = height.saturating_sub(bands.iter().map(|band| band.height()).sum()) / 2;
+        for band in bands {
+            for (x, y, &Luma([luma])) in band.enumerate_pixels() {
+                let color = if luma == 255 { light } else { dark };
+                banner.buffer.put_pixel(height + x, top + y, Rgba(color));
+            }
+            top += band.height();
+        }
+
+        banner.save_as(format, file_path)
+    }
+
+    /// Render an animation cycling through the networks, each shown for its duration,
+    /// e.g. for a display that can only loop a single file
+    ///
+    /// Every frame is a complete code, rendered with the render options of `self`
+    /// so all frames share its colors and size, and centered in a frame of uniform size.
+    /// `format` has to be GIF or, with the `webp` feature, WebP,
+    /// other formats fail with [`GenerationError::NotAnimated`].
+    /// GIF stores durations in 10ms steps, shorter ones are rounded.
+    #[cfg(feature = "image-codecs")]
+    pub fn generate_rotating_animation(
+        &self,
+        networks: &[(Wifi, Duration)],
+        format: ImageFormat,
+        path: &Path,
+    ) -> Result<(), GenerationError> {
+        let images = networks
+            .iter()
+            .map(|(wifi, _)| {
+                let wifi = wifi.clone().with_render_options(self.render_options.clone());
+                wifi.render().map(Rendered::into_rgba)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let frame_width = images.iter().map(|image| image.buffer.width()).max().unwrap_or(0);
+        let frame_height = images.iter().map(|image| image.buffer.height()).max().unwrap_or(0);
+        let background = Rgba(self.pixel_colors().1);
+
+        let frames = images.iter().zip(networks).map(|(image, (_, duration))| {
+            let mut frame = ImageBuffer::from_pixel(frame_width, frame_height, background);
+            image::imageops::replace(
+                &mut frame,
+                &image.buffer,
+                ((frame_width - image.buffer.width()) / 2).into(),
+                ((frame_height - image.buffer.height()) / 2).into(),
+            );
+            (frame, *duration)
+        });
+
+        match format {
+            ImageFormat::ImageFormat(image::ImageFormat::Gif) => {
+                let frames = frames.map(|(frame, duration)| {
+                    image::Frame::from_parts(
+                        frame,
+                        0,
+                        0,
+                        image::Delay::from_saturating_duration(duration),
+                    )
+                });
+                let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+                let mut encoder = image::codecs::gif::GifEncoder::new(file);
+                encoder.set_repeat(image::codecs::gif::Repeat::Infinite)?;
+                encoder.encode_frames(frames)?;
+            }
+            #[cfg(feature = "webp")]
+            ImageFormat::ImageFormat(image::ImageFormat::WebP) => {
+                std::fs::write(path, animated_webp(&frames.collect::<Vec<_>>())?)?;
+            }
+            format => return Err(GenerationError::NotAnimated(format)),
+        }
+
+        Ok(())
+    }
+
+    /// Make the file names generated for this network unique, see [`FileNameSuffix`]
+    pub fn with_file_name_suffix(mut self, suffix: FileNameSuffix) -> Self {
+        self.render_options.file_name_suffix = suffix;
+        self
+    }
+
+    /// The file name used for this network's image, `wifi-<ssid>[-<label>][-<suffix>].<ext>`
+    ///
+    /// With [`FileNameSuffix::Counter`] every call returns a new name.
+    pub fn file_name(&self, label: Option<&str>, format: &ImageFormat) -> String {
+        let mut name = format!("wifi-{}", sanitize_file_name(&self.ssid));
+        if let Some(label) = label {
+            name.push('-');
+            name.push_str(&sanitize_file_name(label));
+        }
+        match &self.render_options.file_name_suffix {
+            FileNameSuffix::None => {}
+            FileNameSuffix::Fingerprint => {
+                name.push('-');
+                name.push_str(&self.fingerprint());
+            }
+            FileNameSuffix::Counter(counter) => {
+                let sequence = counter.fetch_add(1, Ordering::Relaxed);
+                name.push_str(&format!("-{sequence}"));
+            }
+        }
+        format!("{name}.{}", format.extension())
+    }
+
+    /// Bundle everything needed to hand off the network into a single zip archive:
+    /// the PNG image of the QR-Code and, with their features enabled, the SVG (`svg`),
+    /// a printable A4 PDF (`pdf`) and the network as JSON (`serde`).
+    #[cfg(feature = "zip")]
+    pub fn generate_pack(&self, path: &Path) -> Result<(), GenerationError> {
+        use std::io::Write;
+
+        let image = self.render()?;
+        let options = zip::write::FileOptions::default();
+
+        let mut archive = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        archive.start_file("wifi.png", options)?;
+        archive.write_all(&image.to_bytes(ImageFormat::png())?)?;
+        #[cfg(feature = "svg")]
+        {
+            archive.start_file("wifi.svg", options)?;
+            archive.write_all(self.generate_svg_string()?.as_bytes())?;
+        }
+        #[cfg(feature = "pdf")]
+        {
+            archive.start_file("wifi.pdf", options)?;
+            archive.write_all(&crate::pdf::poster_pdf(self)?)?;
+        }
+        #[cfg(feature = "serde")]
+        {
+            archive.start_file("wifi.json", options)?;
+            serde_json::to_writer_pretty(&mut archive, self)?;
+        }
+
+        std::fs::write(path, archive.finish()?.into_inner())?;
+
+        Ok(())
+    }
+
+    fn render(&self) -> Result<Rendered, GenerationError> {
+        self.render_code(&self.qr_code()?)
+    }
+
+    /// draw `code` with the render settings of this network,
+    /// in grayscale unless the colors or a logo need more
+    fn render_code(&self, code: &QrCode) -> Result<Rendered, GenerationError> {
+        let RenderOptions { dark, light, .. } = RenderOptions::default();
+        let colored = self.render_options.colors() != (dark, light)
+            || self.render_options.logo.is_some()
+            || self.render_options.quiet_zone_pattern.is_some();
+        Ok(if colored {
+            Rendered::Color(self.draw(code)?)
+        } else if self.render_options.transparent_background {
+            Rendered::GrayAlpha(self.draw(code)?)
+        } else {
+            Rendered::Gray(self.draw(code)?)
+        })
+    }
+
+    fn draw<P: CodePixel>(&self, code: &QrCode) -> Result<Image<P>, GenerationError> {
+        let (dark, light) = self.pixel_colors();
+        let (dark, light) = (P::from_color(dark), P::from_color(light));
+        let colors = code.to_colors();
+        let quiet_zone = self.render_options.quiet_zone_width();
+        let modules = code.width() as u32 + 2 * quiet_zone;
+        let mut renderer = self.renderer::<Px<P>>(&colors, code.width());
+        renderer.dark_color(Px(dark)).light_color(Px(light));
+
+        #[cfg(feature = "print")]
+        if let Some((width_mm, dpi)) = self.render_options.print_size {
+            let module_size = print_module_size(modules, width_mm, dpi)?;
+            renderer.module_dimensions(module_size, module_size);
+        }
+
+        let mut image = renderer.build();
+        image.png_options = self.render_options.png_options;
+        image.jpeg_options = self.render_options.jpeg_options;
+        let module_size = image.buffer.width() / modules;
+
+        if self.render_options.rounded_finders {
+            round_finder_corners(
+                &mut image.buffer,
+                light,
+                module_size,
+                code.width() as u32,
+                quiet_zone,
+            );
+        }
+
+        // the brackets are drawn one module from the edge, they'd cover the code without room
+        if self.render_options.scan_guide && quiet_zone >= 2 {
+            draw_scan_guide(&mut image.buffer, dark, module_size);
+        }
+
+        #[cfg(feature = "print")]
+        if let Some((width_mm, dpi)) = self.render_options.print_size {
+            let size = (width_mm / MM_PER_INCH * f64::from(dpi)).round() as u32;
+            let mut canvas = ImageBuffer::from_pixel(size, size, light);
+            let offset = (size - image.buffer.width()) / 2;
+            image::imageops::replace(&mut canvas, &image.buffer, offset.into(), offset.into());
+            image.buffer = canvas;
+            image.dpi = Some(dpi);
+        }
+
+        if let Some(tile) = &self.render_options.quiet_zone_pattern {
+            let code_size = code.width() as u32 * module_size;
+            let clear = MIN_QUIET_ZONE * module_size;
+            tile_quiet_zone(&mut image.buffer, light, &tile.to_rgba8(), code_size, clear);
+        }
+
+        if self.render_options.mirror {
+            image::imageops::flip_horizontal_in_place(&mut image.buffer);
+        }
+
+        if let Some((logo, scale)) = &self.render_options.logo {
+            let code_size = code.width() as u32 * module_size;
+            let placed = place_logo(logo, *scale, code_size, image.buffer.dimensions(), self.render_options.mirror);
+            if let Some((left, top, logo)) = placed {
+                if self.render_options.logo_quiet_zone > 0 {
+                    clear_around_logo(
+                        &mut image.buffer,
+                        light,
+                        (left, top, logo.dimensions()),
+                        code_size,
+                        module_size,
+                        self.render_options.logo_quiet_zone,
+                    );
+                }
+                for (x, y, pixel) in logo.enumerate_pixels() {
+                    let under = image.buffer.get_pixel_mut(left + x, top + y);
+                    *under = P::from_color(blend_over(pixel.0, under.color()));
+                }
+            }
+        }
+
+        if let Some(caption) = &self.render_options.caption {
+            let (width, height) = image.buffer.dimensions();
+            let mut band = crate::caption::caption_band(caption, width, module_size);
+            if self.render_options.mirror {
+                image::imageops::flip_horizontal_in_place(&mut band);
+            }
+            let mut canvas = ImageBuffer::from_pixel(width, height + band.height(), light);
+            image::imageops::replace(&mut canvas, &image.buffer, 0, 0);
+            for (x, y, &Luma([luma])) in band.enumerate_pixels() {
+                canvas.put_pixel(x, height + y, if luma == 255 { light } else { dark });
+            }
+            image.buffer = canvas;
+        }
+
+        Ok(image)
+    }
+
+    /// the colors of the dark and the light pixels, see [`RenderOptions::transparent_background`]
+    fn pixel_colors(&self) -> ([u8; 4], [u8; 4]) {
+        let (dark, [r, g, b, a]) = self.render_options.colors();
+        let alpha = if self.render_options.transparent_background { 0 } else { a };
+        (dark, [r, g, b, alpha])
+    }
+}
+
+impl DppConfig {
+    /// Render the URI as a black on white code and write it like [`Wifi::generate_image_file`]
+    pub fn generate_image_file(
+        &self,
+        format: Option<ImageFormat>,
+        file_path: &Path,
+    ) -> Result<ImageFormat, GenerationError> {
+        self.render()?.save_as(format, file_path)
+    }
+
+    /// Render the URI as a black on white code and encode it in memory
+    pub fn generate_image_bytes(&self, format: ImageFormat) -> Result<Vec<u8>, GenerationError> {
+        self.render()?.to_bytes(format)
+    }
+
+    fn render(&self) -> Result<Rendered, GenerationError> {
+        Wifi::new(String::new()).render_code(&QrCode::new(self.to_string())?)
+    }
+}
+
+/// Render all networks side by side into a single horizontal spritesheet,
+/// e.g. for a display cycling through several networks.
+///
+/// Every code is centered in a frame of uniform size,
+/// the position and SSID of each frame are written to a JSON manifest at `manifest_path`.
+/// The space around smaller codes is filled with the light color of the first network.
+pub fn generate_spritesheet(
+    networks: &[Wifi],
+    format: Option<ImageFormat>,
+    image_path: &Path,
+    manifest_path: &Path,
+) -> Result<(), GenerationError> {
+    let (sheet, (frame_width, frame_height)) = render_tiled(networks, networks.len() as u32, 1, 0)?;
+    sheet.save_as(format, image_path)?;
+
+    let frames = networks
+        .iter()
+        .enumerate()
+        .map(|(index, wifi)| {
+            let x = frame_width * index as u32;
+            let ssid = json_string(&wifi.ssid);
+            format!(
+                r#"{{"index":{index},"ssid":{ssid},"x":{x},"y":0,"w":{frame_width},"h":{frame_height}}}"#
+            )
+        })
+        .collect::<Vec<_>>();
+
+    std::fs::write(manifest_path, format!(r#"{{"frames":[{}]}}"#, frames.join(",")))?;
+
+    Ok(())
+}
+
+/// Render the networks row by row into a grid of `cols` by `rows` cells, `gap` pixels apart,
+/// e.g. to print a sheet of codes to cut apart
+///
+/// Every code is centered in a cell of uniform size, cells without a network stay blank
+/// and the gaps are filled with the light color of the first network.
+/// Fails with [`GenerationError::GridTooSmall`] if there are more networks than cells.
+pub fn generate_grid(
+    networks: &[Wifi],
+    cols: usize,
+    rows: usize,
+    gap: u32,
+) -> Result<RenderedImage, GenerationError> {
+    let cells = cols.saturating_mul(rows);
+    if networks.len() > cells {
+        return Err(GenerationError::GridTooSmall {
+            networks: networks.len(),
+            cells,
+        });
+    }
+
+    let (sheet, _) = render_tiled(networks, cols as u32, rows as u32, gap)?;
+    Ok(RenderedImage(sheet))
+}
+
+/// Render the networks and tile them like [`tile`], in grayscale if all of them are,
+/// returns the sheet and the size of a cell
+fn render_tiled(
+    networks: &[Wifi],
+    cols: u32,
+    rows: u32,
+    gap: u32,
+) -> Result<(Rendered, (u32, u32)), GenerationError> {
+    let images = networks
+        .iter()
+        .map(Wifi::render)
+        .collect::<Result<Vec<_>, _>>()?;
+    let background = networks.first().map_or([255; 4], |first| first.pixel_colors().1);
+
+    let gray = images
+        .iter()
+        .map(|image| match image {
+            Rendered::Gray(image) => Some(image),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>();
+    match gray {
+        Some(images) => {
+            let (sheet, cell) = tile(&images, Luma::from_color(background), cols, rows, gap);
+            Ok((Rendered::Gray(sheet), cell))
+        }
+        None => {
+            let images = images.into_iter().map(Rendered::into_rgba).collect::<Vec<_>>();
+            let images = images.iter().collect::<Vec<_>>();
+            let (sheet, cell) = tile(&images, Rgba(background), cols, rows, gap);
+            Ok((Rendered::Color(sheet), cell))
+        }
+    }
+}
+
+/// Center each image in a cell of uniform size, row by row in `cols` by `rows` cells
+/// `gap` pixels apart, returns the sheet and the size of a cell
+///
+/// The sheet is encoded with the settings of the first image.
+fn tile<P: CodePixel>(
+    images: &[&Image<P>],
+    background: P,
+    cols: u32,
+    rows: u32,
+    gap: u32,
+) -> (Image<P>, (u32, u32)) {
+    let cell_width = images.iter().map(|image| image.buffer.width()).max().unwrap_or(0);
+    let cell_height = images.iter().map(|image| image.buffer.height()).max().unwrap_or(0);
+
+    let mut sheet = Image::new(ImageBuffer::from_pixel(
+        cols * cell_width + cols.saturating_sub(1) * gap,
+        rows * cell_height + rows.saturating_sub(1) * gap,
+        background,
+    ));
+    if let Some(first) = images.first() {
+        sheet.png_options = first.png_options;
+        sheet.jpeg_options = first.jpeg_options;
+    }
+
+    for (index, image) in images.iter().enumerate() {
+        let (col, row) = (index as u32 % cols, index as u32 / cols);
+        let x = col * (cell_width + gap) + (cell_width - image.buffer.width()) / 2;
+        let y = row * (cell_height + gap) + (cell_height - image.buffer.height()) / 2;
+        image::imageops::replace(&mut sheet.buffer, &image.buffer, x.into(), y.into());
+    }
+
+    (sheet, (cell_width, cell_height))
+}
+
+/// Write the image of each network to `dir`, named by [`Wifi::file_name`]
+///
+/// A failing network doesn't stop the batch,
+/// the result for each network is returned in order, with the path written on success.
+pub fn generate_batch(
+    networks: impl Iterator<Item = Wifi>,
+    dir: &Path,
+    format: ImageFormat,
+) -> Vec<Result<PathBuf, GenerationError>> {
+    networks
+        .map(|wifi| {
+            let path = dir.join(wifi.file_name(None, &format));
+            wifi.generate_image_file(Some(format.clone()), &path)?;
+            Ok(path)
+        })
+        .collect()
+}
+
+/// Write one JSON object per line and network to `writer`, as soon as its image is encoded:
+/// `{"ssid":"...","image":"<base64>"}`
+///
+/// The password (or hex PSK) is only added as `"password"` with `include_password`.
+/// A failing network doesn't stop the batch and gets no line,
+/// the result for each network is returned in order.
+pub fn generate_batch_ndjson(
+    networks: &[Wifi],
+    format: ImageFormat,
+    include_password: bool,
+    mut writer: impl std::io::Write,
+) -> Vec<Result<(), GenerationError>> {
+    use base64::Engine;
+
+    networks
+        .iter()
+        .map(|wifi| {
+            let image = wifi.render()?.to_bytes(format.clone())?;
+            let image = base64::engine::general_purpose::STANDARD.encode(image);
+
+            let mut line = format!(r#"{{"ssid":{}"#, json_string(&wifi.ssid));
+            if include_password {
+                let psk = wifi
+                    .psk
+                    .map(|psk| psk.iter().map(|b| format!("{b:02x}")).collect::<String>());
+                if let Some(password) = wifi.password.as_ref().or(psk.as_ref()) {
+                    line.push_str(&format!(r#","password":{}"#, json_string(password)));
+                }
+            }
+            line.push_str(&format!(r#","image":"{image}"}}"#));
+
+            writeln!(writer, "{line}")?;
+            writer.flush()?;
+            Ok(())
+        })
+        .collect()
+}
+
+/// Quote and escape a string for JSON
+fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// Assemble an animated, lossless WebP from frames of the same size,
+/// the `AnimEncoder` of the webp crate can't set the duration of the last frame
+#[cfg(all(feature = "webp", feature = "image-codecs"))]
+fn animated_webp(frames: &[(RgbaImage, Duration)]) -> Result<Vec<u8>, GenerationError> {
+    fn chunk(out: &mut Vec<u8>, name: &[u8; 4], data: &[u8]) {
+        out.extend_from_slice(name);
+        out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        out.extend_from_slice(data);
+        if data.len() % 2 == 1 {
+            out.push(0);
+        }
+    }
+    fn u24(out: &mut Vec<u8>, value: u32) {
+        out.extend_from_slice(&value.min(0xff_ffff).to_le_bytes()[..3]);
+    }
+
+    let (width, height) = frames.first().map_or((1, 1), |(frame, _)| frame.dimensions());
+
+    let mut body = Vec::new();
+    let mut header = vec![0x02 | 0x10, 0, 0, 0]; // animation and alpha
+    u24(&mut header, width - 1);
+    u24(&mut header, height - 1);
+    chunk(&mut body, b"VP8X", &header);
+    // transparent background, loop forever
+    chunk(&mut body, b"ANIM", &[0, 0, 0, 0, 0, 0]);
+
+    for (frame, duration) in frames {
+        let encoded = webp::Encoder::from_rgba(frame, width, height)
+            .encode_simple(true, 75.0)
+            .map_err(GenerationError::WebPError)?;
+        let mut data = Vec::new();
+        u24(&mut data, 0);
+        u24(&mut data, 0);
+        u24(&mut data, width - 1);
+        u24(&mut data, height - 1);
+        u24(&mut data, duration.as_millis().try_into().unwrap_or(u32::MAX));
+        // replace the previous frame instead of blending over it
+        data.push(0x02);
+        // only the image chunks of the encoded file, after its 12 byte RIFF header
+        let mut rest = &encoded[12..];
+        while rest.len() >= 8 {
+            let size = u32::from_le_bytes([rest[4], rest[5], rest[6], rest[7]]) as usize;
+            let end = (8 + size + size % 2).min(rest.len());
+            if matches!(&rest[..4], b"ALPH" | b"VP8 " | b"VP8L") {
+                data.extend_from_slice(&rest[..end]);
+            }
+            rest = &rest[end..];
+        }
+        chunk(&mut body, b"ANMF", &data);
+    }
+
+    let mut webp = Vec::with_capacity(body.len() + 12);
+    webp.extend_from_slice(b"RIFF");
+    webp.extend_from_slice(&(body.len() as u32 + 4).to_le_bytes());
+    webp.extend_from_slice(b"WEBP");
+    webp.extend_from_slice(&body);
+    Ok(webp)
+}
+
+/// Largest share of the code width a logo may cover, more hides too many modules to decode
+const MAX_LOGO_SCALE: f32 = 0.2;
+
+/// Scale the logo to fit into `scale` of the code width and center it in the image
+fn place_logo(
+    logo: &DynamicImage,
+    scale: f32,
+    code_size: u32,
+    (width, height): (u32, u32),
+    mirror: bool,
+) -> Option<(u32, u32, RgbaImage)> {
+    let max_size = (code_size as f32 * scale.clamp(0.0, MAX_LOGO_SCALE)) as u32;
+    if max_size == 0 || logo.width() == 0 || logo.height() == 0 {
+        return None;
+    }
+    let mut logo = logo
+        .resize(max_size, max_size, image::imageops::FilterType::Triangle)
+        .into_rgba8();
+    if mirror {
+        image::imageops::flip_horizontal_in_place(&mut logo);
+    }
+    Some(((width - logo.width()) / 2, (height - logo.height()) / 2, logo))
+}
+
+/// Fill the modules under the logo and `border` modules around it with `light`,
+/// the code of `code_size` pixels being centered in `buffer`
+fn clear_around_logo<P: CodePixel>(
+    buffer: &mut ImageBuffer<P, Vec<u8>>,
+    light: P,
+    (left, top, (width, height)): (u32, u32, (u32, u32)),
+    code_size: u32,
+    module_size: u32,
+    border: u32,
+) {
+    let origin_x = (buffer.width() - code_size) / 2;
+    let origin_y = (buffer.height() - code_size) / 2;
+    // whole modules, so no module is left partially drawn
+    let span = |origin: u32, start: u32, len: u32| {
+        let first = ((start - origin) / module_size).saturating_sub(border);
+        let end =
+            ((start - origin + len).div_ceil(module_size) + border).min(code_size / module_size);
+        (origin + first * module_size)..(origin + end * module_size)
+    };
+    for y in span(origin_y, top, height) {
+        for x in span(origin_x, left, width) {
+            buffer.put_pixel(x, y, light);
+        }
+    }
+}
+
+/// The quiet zone width in modules the QR code spec requires
+const MIN_QUIET_ZONE: u32 = 4;
+
+/// Blend `tile`, repeated from the top left corner, over the `light` pixels of `buffer`
+/// that are more than `clear` pixels away from the code of `code_size` pixels centered in it
+fn tile_quiet_zone<P: CodePixel>(
+    buffer: &mut ImageBuffer<P, Vec<u8>>,
+    light: P,
+    tile: &RgbaImage,
+    code_size: u32,
+    clear: u32,
+) {
+    if tile.width() == 0 || tile.height() == 0 {
+        return;
+    }
+    let inner = |size: u32| {
+        let start = (size - code_size) / 2;
+        start.saturating_sub(clear)..start + code_size + clear
+    };
+    let (inner_x, inner_y) = (inner(buffer.width()), inner(buffer.height()));
+    for (x, y, pixel) in buffer.enumerate_pixels_mut() {
+        let clear = inner_x.contains(&x) && inner_y.contains(&y);
+        if clear || pixel.color() != light.color() {
+            continue;
+        }
+        let over = tile.get_pixel(x % tile.width(), y % tile.height());
+        *pixel = P::from_color(blend_over(over.0, light.color()));
+    }
+}
+
+/// Composite `src` over `dst`, both straight (not premultiplied) RGBA
+fn blend_over(src: [u8; 4], dst: [u8; 4]) -> [u8; 4] {
+    let src_alpha = u32::from(src[3]);
+    let dst_alpha = u32::from(dst[3]) * (255 - src_alpha) / 255;
+    let alpha = src_alpha + dst_alpha;
+    if alpha == 0 {
+        return [0; 4];
+    }
+    let channel =
+        |i: usize| ((u32::from(src[i]) * src_alpha + u32::from(dst[i]) * dst_alpha) / alpha) as u8;
+    [channel(0), channel(1), channel(2), alpha as u8]
+}
+
+#[cfg(feature = "print")]
+const MM_PER_INCH: f64 = 25.4;
+
+/// Smallest printed module size phone cameras reliably scan from a normal distance
+#[cfg(feature = "print")]
+const MIN_PRINT_MODULE_MM: f64 = 0.3;
+
+/// The largest whole-pixel module size so that `modules` modules fit into `width_mm` at `dpi`
+#[cfg(feature = "print")]
+fn print_module_size(modules: u32, width_mm: f64, dpi: u32) -> Result<u32, GenerationError> {
+    let width_px = width_mm / MM_PER_INCH * f64::from(dpi);
+    let module_size = (width_px / f64::from(modules)).floor() as u32;
+    let module_mm = f64::from(module_size) / f64::from(dpi) * MM_PER_INCH;
+
+    if module_size == 0 || module_mm < MIN_PRINT_MODULE_MM {
+        return Err(GenerationError::ModuleTooSmall { module_mm });
+    }
+
+    Ok(module_size)
+}
+
+/// Clear the pixels outside a quarter circle of one module radius
+/// at each outer corner of the three 7x7 finder patterns
+fn round_finder_corners<P: CodePixel>(
+    buffer: &mut ImageBuffer<P, Vec<u8>>,
+    light: P,
+    module_size: u32,
+    modules: u32,
+    quiet_zone: u32,
+) {
+    let radius = module_size as f32;
+    let finder_size = 7 * module_size;
+
+    for (finder_x, finder_y) in [(0, 0), (modules - 7, 0), (0, modules - 7)] {
+        let left = (finder_x + quiet_zone) * module_size;
+        let top = (finder_y + quiet_zone) * module_size;
+
+        for (corner_x, corner_y) in [
+            (left, top),
+            (left + finder_size - module_size, top),
+            (left, top + finder_size - module_size),
+            (left + finder_size - module_size, top + finder_size - module_size),
+        ] {
+            // the center of the quarter circle is the inner corner of the corner module
+            let center_x = if corner_x == left { left + module_size } else { corner_x } as f32;
+            let center_y = if corner_y == top { top + module_size } else { corner_y } as f32;
+
+            for y in corner_y..corner_y + module_size {
+                for x in corner_x..corner_x + module_size {
+                    let dx = x as f32 + 0.5 - center_x;
+                    let dy = y as f32 + 0.5 - center_y;
+                    if dx.hypot(dy) > radius {
+                        buffer.put_pixel(x, y, light);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Draw an L-shaped bracket into each corner of the quiet zone,
+/// one module in from the edge and a quarter module thick
+fn draw_scan_guide<P: CodePixel>(buffer: &mut ImageBuffer<P, Vec<u8>>, dark: P, module_size: u32) {
+    let (width, height) = buffer.dimensions();
+    let inset = module_size;
+    let thickness = (module_size / 4).max(1);
+    let length = width.min(height) / 5;
+
+    for (left, top) in [(true, true), (false, true), (true, false), (false, false)] {
+        let x = if left { inset } else { width - inset - length };
+        let y = if top { inset } else { height - inset - length };
+        let corner_x = if left { inset } else { width - inset - thickness };
+        let corner_y = if top { inset } else { height - inset - thickness };
+
+        for offset in 0..length {
+            for depth in 0..thickness {
+                buffer.put_pixel(x + offset, corner_y + depth, dark);
+                buffer.put_pixel(corner_x + depth, y + offset, dark);
+            }
+        }
+    }
+}
+
+/// The format for the extension of `file_path`
+fn guess_format(file_path: &Path) -> Result<ImageFormat, GenerationError> {
+    file_path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(ImageFormat::from_extension)
+        .ok_or_else(|| GenerationError::UnknownImageExtension(file_path.to_path_buf()))
+}
+
+/// Replace characters that are not safe to use in a file name
+fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "png-only")]
+    #[test]
+    fn png_only_output_decodes() {
+        let wifi = Wifi::new("Test".to_string());
+        let bytes = wifi.generate_image_bytes(ImageFormat::png()).unwrap();
+        let Rendered::Gray(image) = wifi.render().unwrap() else {
+            panic!("black on white renders in grayscale");
+        };
+
+        let mut reader = png::Decoder::new(bytes.as_slice()).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+
+        assert_eq!(info.color_type, png::ColorType::Grayscale);
+        assert_eq!((info.width, info.height), image.buffer.dimensions());
+        assert_eq!(
+            &pixels[..info.buffer_size()],
+            image.buffer.as_raw().as_slice()
+        );
+    }
+
+    #[cfg(feature = "zip")]
+    #[test]
+    fn pack_entries() {
+        let path = std::env::temp_dir().join(format!("wifi-qr-pack-{}.zip", std::process::id()));
+        Wifi::new("Test".to_string()).generate_pack(&path).unwrap();
+
+        let archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let mut entries: Vec<_> = archive.file_names().collect();
+        entries.sort_unstable();
+        std::fs::remove_file(&path).unwrap();
+
+        let mut expected = vec!["wifi.png"];
+        if cfg!(feature = "svg") {
+            expected.push("wifi.svg");
+        }
+        if cfg!(feature = "pdf") {
+            expected.push("wifi.pdf");
+        }
+        if cfg!(feature = "serde") {
+            expected.push("wifi.json");
+        }
+        expected.sort_unstable();
+        assert_eq!(entries, expected);
+    }
+
+    #[cfg(feature = "image-codecs")]
+    #[test]
+    fn luma_alpha_png() {
+        let bytes = Wifi::new("Test".to_string())
+            .with_luma_alpha_transparency(true)
+            .generate_image_bytes(ImageFormat::png())
+            .unwrap();
+        let image::DynamicImage::ImageLumaA8(image) = image::load_from_memory(&bytes).unwrap()
+        else {
+            panic!("expected a LumaA PNG");
+        };
+
+        // the quiet zone is transparent, the corner of the top left finder pattern opaque black
+        assert_eq!(image.get_pixel(0, 0).0[1], 0);
+        assert_eq!(image.get_pixel(32, 32).0, [0, 255]);
+        assert!(image
+            .pixels()
+            .all(|pixel| matches!(pixel.0, [0, 255] | [_, 0])));
+    }
+
+    #[cfg(feature = "qoi")]
+    #[test]
+    fn qoi_round_trip() {
+        let Rendered::Gray(image) = Wifi::new("Test".to_string()).render().unwrap() else {
+            panic!("black on white renders in grayscale");
+        };
+        let bytes = image.to_bytes(ImageFormat::Qoi).unwrap();
+
+        let (header, pixels) = arqoii::QoiDecoder::new(bytes.into_iter()).unwrap();
+        assert_eq!(header.channels, arqoii::types::QoiChannels::Rgb);
+        assert_eq!((header.width, header.height), image.buffer.dimensions());
+        let expected = image.buffer.pixels().map(|&Luma([l])| arqoii::Pixel {
+            r: l,
+            g: l,
+            b: l,
+            a: 255,
+        });
+        assert!(pixels.eq(expected));
+    }
+
+    #[cfg(feature = "qoi")]
+    #[test]
+    fn qoi_size() {
+        let Rendered::Gray(image) = Wifi::new("Test".to_string()).render().unwrap() else {
+            panic!("black on white renders in grayscale");
+        };
+        let rgb = image.to_bytes(ImageFormat::Qoi).unwrap();
+        let (width, height) = image.buffer.dimensions();
+        let rgba: Vec<u8> = arqoii::QoiEncoder::new(
+            QoiHeader::new(
+                width,
+                height,
+                arqoii::types::QoiChannels::Rgba,
+                arqoii::types::QoiColorSpace::SRgbWithLinearAlpha,
+            ),
+            image.buffer.pixels().map(|&Luma([l])| arqoii::Pixel {
+                r: l,
+                g: l,
+                b: l,
+                a: 255,
+            }),
+        )
+        .collect();
+
+        let (header, pixels) = arqoii::QoiDecoder::new(rgb.clone().into_iter()).unwrap();
+        assert_eq!((header.width, header.height), (width, height));
+        assert_eq!(pixels.count(), (width * height) as usize);
+        // equal channels are all runs and index hits, no bigger than the RGBA encoding
+        assert!(rgb.len() <= rgba.len(), "{} > {}", rgb.len(), rgba.len());
+    }
+
+    #[test]
+    fn logo_quiet_zone() {
+        const RED: [u8; 4] = [255, 0, 0, 255];
+        let logo = DynamicImage::ImageRgba8(ImageBuffer::from_pixel(10, 10, Rgba(RED)));
+        let image = Wifi::new("Test".to_string())
+            .with_logo(logo, 0.2)
+            .with_logo_quiet_zone(1)
+            .generate_rgba_image()
+            .unwrap();
+
+        let red = image
+            .enumerate_pixels()
+            .filter(|(_, _, pixel)| pixel.0 == RED)
+            .map(|(x, y, _)| (x, y));
+        let (left, top) = red.clone().min().unwrap();
+        let (right, bottom) = red.max().unwrap();
+
+        // one module of 8 pixels around the logo is light
+        for y in top - 8..=bottom + 8 {
+            for x in left - 8..=right + 8 {
+                let inside = (left..=right).contains(&x) && (top..=bottom).contains(&y);
+                let expected = if inside { RED } else { [255; 4] };
+                assert_eq!(image.get_pixel(x, y).0, expected, "({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn quiet_zone_pattern() {
+        const RED: [u8; 4] = [255, 0, 0, 255];
+        let tile = DynamicImage::ImageRgba8(ImageBuffer::from_pixel(3, 3, Rgba(RED)));
+        let image = Wifi::new("Test".to_string())
+            .with_border(Some(6))
+            .with_quiet_zone_pattern(tile)
+            .generate_rgba_image()
+            .unwrap();
+
+        // 8 pixel modules, the outer 2 of the 6 border modules are tiled
+        let (width, height) = image.dimensions();
+        let (tiled, clear) = (2 * 8, 6 * 8);
+        for (x, y) in [(0, 0), (tiled - 1, height / 2), (width / 2, height - 1)] {
+            assert_eq!(image.get_pixel(x, y).0, RED, "({x}, {y})");
+        }
+        // the 4 modules scanners need around the code stay the background color
+        for i in tiled..clear {
+            for (x, y) in [(i, i), (i, height / 2), (width / 2, i), (width - 1 - i, i)] {
+                assert_eq!(image.get_pixel(x, y).0, [255; 4], "({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn logo_ec_level() {
+        let logo = DynamicImage::ImageRgba8(ImageBuffer::from_pixel(10, 10, Rgba([255; 4])));
+        let wifi = Wifi::new("Test".to_string()).with_logo(logo, 0.2);
+        assert_eq!(wifi.render_report().unwrap().ec_level, qrcode::EcLevel::H);
+
+        // an explicit level wins over the default for logos
+        let wifi = wifi.with_ec_level(Some(qrcode::EcLevel::Q));
+        assert_eq!(wifi.render_report().unwrap().ec_level, qrcode::EcLevel::Q);
+        assert!(!wifi.render_report().unwrap().ec_level_raised);
+
+        let wifi = Wifi::new("Test".to_string()).with_caption(Some("Test".to_string()));
+        let report = wifi.render_report().unwrap();
+        assert_eq!(report.ec_level, qrcode::EcLevel::Q);
+        assert!(report.ec_level_raised);
+        assert!(report
+            .to_string()
+            .contains("error correction Q (raised for the overlays)"));
+
+        let wifi = wifi.with_ec_level(Some(qrcode::EcLevel::L));
+        assert_eq!(wifi.render_report().unwrap().ec_level, qrcode::EcLevel::L);
+    }
+
+    #[test]
+    fn format_from_name() {
+        let name = |name| ImageFormat::from_name(name).map(|format| format!("{format:?}"));
+        assert_eq!(name("png").as_deref(), Some("Png"));
+        assert_eq!(name("PNG").as_deref(), Some("Png"));
+        assert_eq!(
+            name("qoi").as_deref(),
+            cfg!(feature = "qoi").then_some("Qoi")
+        );
+        assert_eq!(name("tiff"), None);
+        assert_eq!(name(""), None);
+        assert_eq!(
+            ImageFormat::from_extension("Png").map(|format| format.content_type()),
+            Some("image/png")
+        );
+    }
+
+    #[test]
+    fn grayscale_output() {
+        let wifi = Wifi::new("Test".to_string())
+            .with_method(Some(crate::WifiMethod::Wpa))
+            .with_password(Some("password".to_string()));
+        let Rendered::Gray(image) = wifi.render().unwrap() else {
+            panic!("black on white renders in grayscale");
+        };
+
+        // the modules drawn straight from the code, black on white with a 4 module quiet zone
+        let code = wifi.qr_code().unwrap();
+        let (width, colors) = (code.width() as u32, code.to_colors());
+        let module_size = image.buffer.width() / (width + 8);
+        let expected = ImageBuffer::from_fn(image.buffer.width(), image.buffer.height(), |x, y| {
+            let (x, y) = (x / module_size, y / module_size);
+            let inside = (4..width + 4).contains(&x) && (4..width + 4).contains(&y);
+            let dark = inside && colors[((y - 4) * width + x - 4) as usize] == qrcode::Color::Dark;
+            Luma([if dark { 0 } else { 255 }])
+        });
+        assert_eq!(image.buffer.as_raw(), expected.as_raw());
+
+        #[cfg(feature = "image-codecs")]
+        {
+            let encode = |image: &Image<Luma<u8>>| {
+                let mut bytes = std::io::Cursor::new(Vec::new());
+                image
+                    .write_image(image::ImageFormat::Png, &mut bytes)
+                    .unwrap();
+                bytes.into_inner()
+            };
+            assert_eq!(encode(&image), encode(&Image::new(expected)));
+        }
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn cli_format_names() {
+        let parse =
+            |name: &str| ImageFormat::from_str(name, false).map(|format| format!("{format:?}"));
+        assert_eq!(parse("png").as_deref(), Ok("Png"));
+        #[cfg(feature = "qoi")]
+        assert_eq!(parse("qoi").as_deref(), Ok("Qoi"));
+        #[cfg(feature = "image-codecs")]
+        {
+            assert_eq!(parse("jpeg").as_deref(), Ok("Jpeg"));
+            assert_eq!(parse("bmp").as_deref(), Ok("Bmp"));
+            assert_eq!(parse("gif").as_deref(), Ok("Gif"));
+        }
+        #[cfg(feature = "webp")]
+        assert_eq!(parse("webp").as_deref(), Ok("WebP"));
+        assert!(parse("tiff").is_err());
+
+        // every advertised name parses back to its format
+        for format in ImageFormat::value_variants() {
+            let value = format.to_possible_value().unwrap();
+            let name = value.get_name();
+            assert_eq!(name, name.to_lowercase());
+            assert_eq!(parse(name), Ok(format!("{format:?}")));
+        }
+    }
+
+    #[cfg(feature = "image-codecs")]
+    #[test]
+    fn banner() {
+        let path = std::env::temp_dir().join(format!("wifi-qr-banner-{}.png", std::process::id()));
+        Wifi::new("Test".to_string())
+            .with_method(Some(crate::WifiMethod::Wpa))
+            .with_password(Some("password".to_string()))
+            .generate_banner(960, 240, true, None, &path)
+            .unwrap();
+        let image = image::open(&path).unwrap().into_luma8();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(image.dimensions(), (960, 240));
+
+        let dark_pixels = |columns: std::ops::Range<u32>| {
+            image
+                .enumerate_pixels()
+                .filter(move |&(x, _, pixel)| columns.contains(&x) && pixel.0 == [0])
+                .map(|(x, y, _)| (x, y))
+        };
+        let span = |values: Vec<u32>| values.iter().max().unwrap() - values.iter().min().unwrap();
+
+        // the code is square and fills most of the height
+        let (xs, ys) = dark_pixels(0..240).unzip();
+        let (code_width, code_height) = (span(xs), span(ys));
+        assert_eq!(code_width, code_height);
+        assert!(code_height > 120, "{code_height}");
+
+        // the text is drawn next to it
+        assert!(dark_pixels(240..960).next().is_some());
+    }
+
+    #[cfg(feature = "image-codecs")]
+    #[test]
+    fn image_bytes_decode() {
+        let wifi = Wifi::new("Test".to_string());
+        let dimensions = wifi.render().unwrap().into_rgba().buffer.dimensions();
+        for name in ["png", "bmp", "gif"] {
+            let format = ImageFormat::from_name(name).unwrap();
+            let bytes = wifi.generate_image_bytes(format).unwrap();
+            let image = image::load_from_memory(&bytes).unwrap();
+            assert_eq!((image.width(), image.height()), dimensions, "{name}");
+        }
+    }
+
+    #[test]
+    fn transparent_corner() {
+        let wifi = Wifi::new("Test".to_string()).with_render_options(RenderOptions {
+            dark: [0x12, 0x34, 0x56, 255],
+            transparent_background: true,
+            ..RenderOptions::default()
+        });
+        let image = wifi.generate_rgba_image().unwrap();
+        assert_eq!(image.get_pixel(0, 0).0[3], 0);
+        // the corner of the top left finder pattern stays opaque
+        assert_eq!(image.get_pixel(32, 32).0, [0x12, 0x34, 0x56, 255]);
+
+        // what JPEG gets, as it has no alpha channel
+        let flattened = wifi.render().unwrap().into_rgba().flattened();
+        assert_eq!(flattened.buffer.get_pixel(0, 0).0, [255; 4]);
+    }
+
+    #[test]
+    fn module_size() {
+        let width = |wifi: Wifi| wifi.generate_rgba_image().unwrap().width();
+        let wifi = Wifi::new("Test".to_string());
+        let modules = width(wifi.clone().with_module_size(1));
+
+        assert_eq!(width(wifi.clone()), 8 * modules);
+        assert_eq!(width(wifi.clone().with_module_size(10)), 10 * modules);
+        // the module size grows to reach the minimum, but never shrinks
+        assert_eq!(
+            width(wifi.clone().with_min_dimensions(20 * modules - 1, 0)),
+            20 * modules
+        );
+        assert_eq!(
+            width(wifi.with_module_size(10).with_min_dimensions(1, 1)),
+            10 * modules
+        );
+    }
+
+    #[cfg(any(feature = "image-codecs", feature = "png-only"))]
+    #[test]
+    fn band_pair() {
+        let dir = std::env::temp_dir().join(format!("wifi-qr-bands-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let wifi = Wifi::new("Venue".to_string())
+            .with_method(Some(crate::WifiMethod::Wpa))
+            .with_password(Some("password".to_string()))
+            .with_border(Some(2));
+
+        let paths = wifi
+            .generate_band_pair("Venue", "Venue-5G", &dir, ImageFormat::png())
+            .unwrap();
+        let names = paths.each_ref().map(|path| path.file_name().unwrap());
+        assert_eq!(names, ["wifi-Venue-2.4GHz.png", "wifi-Venue-5G-5GHz.png"]);
+
+        // each image is the shared network with its own SSID and band caption
+        let bands = [("Venue", "2.4GHz"), ("Venue-5G", "5GHz")];
+        for (path, (ssid, band)) in paths.iter().zip(bands) {
+            let expected = Wifi {
+                ssid: ssid.to_string(),
+                ..wifi.clone()
+            }
+            .with_caption(Some(band.to_string()));
+            let bytes = expected.generate_image_bytes(ImageFormat::png()).unwrap();
+            assert_eq!(std::fs::read(path).unwrap(), bytes, "{band}");
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(any(feature = "image-codecs", feature = "png-only"))]
+    #[test]
+    fn save_matches_writer() {
+        let rendered = Wifi::new("Test".to_string()).render().unwrap();
+        let path = std::env::temp_dir().join(format!("wifi-qr-save-{}", std::process::id()));
+
+        let check = |format: ImageFormat| {
+            rendered.save_as(Some(format.clone()), &path).unwrap();
+            let mut written = Vec::new();
+            rendered
+                .save_to_writer(format.clone(), &mut written)
+                .unwrap();
+            assert_eq!(std::fs::read(&path).unwrap(), written, "{format:?}");
+        };
+        check(ImageFormat::png());
+        #[cfg(feature = "qoi")]
+        check(ImageFormat::qoi());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn other_formats_need_image_codecs() {
+        assert!(ImageFormat::from_name("png").is_some());
+        for name in ["jpeg", "bmp", "gif"] {
+            assert_eq!(
+                ImageFormat::from_name(name).is_some(),
+                cfg!(feature = "image-codecs"),
+                "{name}"
+            );
+        }
+    }
+
+    #[cfg(feature = "image-codecs")]
+    fn rotating_networks() -> [(Wifi, Duration); 3] {
+        [
+            (Wifi::new("First".to_string()), Duration::from_millis(100)),
+            (Wifi::new("Second".to_string()), Duration::from_millis(250)),
+            (Wifi::new("Third".to_string()), Duration::from_secs(1)),
+        ]
+    }
+
+    #[cfg(feature = "image-codecs")]
+    #[test]
+    fn rotating_gif() {
+        use image::AnimationDecoder;
+
+        let path =
+            std::env::temp_dir().join(format!("wifi-qr-rotating-{}.gif", std::process::id()));
+        let networks = rotating_networks();
+        let style = Wifi::new(String::new()).with_module_size(4);
+        style
+            .generate_rotating_animation(
+                &networks,
+                ImageFormat::ImageFormat(image::ImageFormat::Gif),
+                &path,
+            )
+            .unwrap();
+
+        let file = std::io::BufReader::new(std::fs::File::open(&path).unwrap());
+        let frames = image::codecs::gif::GifDecoder::new(file)
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // rendered with the module size of the style
+        let width = networks
+            .iter()
+            .map(|(wifi, _)| {
+                let wifi = wifi.clone().with_module_size(4);
+                wifi.render().unwrap().into_rgba().buffer.width()
+            })
+            .max();
+
+        assert_eq!(frames.len(), networks.len());
+        for (frame, (_, duration)) in frames.iter().zip(&networks) {
+            let (numer, denom) = frame.delay().numer_denom_ms();
+            assert_eq!(Duration::from_millis(u64::from(numer / denom)), *duration);
+            assert_eq!(Some(frame.buffer().width()), width);
+        }
+        assert_ne!(frames[0].buffer(), frames[1].buffer());
+
+        let png = style.generate_rotating_animation(&networks, ImageFormat::png(), &path);
+        assert!(matches!(png, Err(GenerationError::NotAnimated(_))));
+    }
+
+    #[cfg(all(feature = "webp", feature = "image-codecs"))]
+    #[test]
+    fn rotating_webp() {
+        let path =
+            std::env::temp_dir().join(format!("wifi-qr-rotating-{}.webp", std::process::id()));
+        let networks = rotating_networks();
+        Wifi::new(String::new())
+            .generate_rotating_animation(&networks, ImageFormat::webp(), &path)
+            .unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let animation = webp::AnimDecoder::new(&bytes).decode().unwrap();
+
+        assert_eq!(animation.len(), networks.len());
+        // the decoder reports when each frame ends
+        let ends: Vec<_> = animation
+            .into_iter()
+            .map(|frame| frame.get_time_ms())
+            .collect();
+        assert_eq!(ends, [100, 350, 1350]);
+    }
+
+    #[test]
+    fn grid_dimensions() {
+        let networks = [
+            Wifi::new("Room 1".to_string()),
+            Wifi::new("Room 2".to_string()),
+            Wifi::new("Room 3".to_string()),
+        ];
+        let Rendered::Gray(code) = networks[0].render().unwrap() else {
+            panic!("black on white renders in grayscale");
+        };
+        let (width, height) = code.buffer.dimensions();
+
+        // the fourth cell stays blank
+        let grid = generate_grid(&networks, 2, 2, 10).unwrap();
+        assert_eq!(grid.dimensions(), (2 * width + 10, 2 * height + 10));
+        let grid = grid.to_dynamic().into_luma8();
+        assert!(grid
+            .enumerate_pixels()
+            .filter(|(x, y, _)| *x >= width + 10 && *y >= height + 10)
+            .all(|(_, _, pixel)| pixel.0 == [255]));
+
+        assert!(matches!(
+            generate_grid(&networks, 1, 2, 0),
+            Err(GenerationError::GridTooSmall {
+                networks: 3,
+                cells: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn mirror() {
+        let wifi = Wifi::new("Test".to_string());
+        let normal = wifi.generate_rgba_image().unwrap();
+        let mirrored = wifi.with_mirror(true).generate_rgba_image().unwrap();
+
+        assert_ne!(mirrored, normal);
+        // flipping it back gives the normal code
+        assert_eq!(image::imageops::flip_horizontal(&mirrored), normal);
+    }
+
+    #[cfg(any(feature = "image-codecs", feature = "png-only"))]
+    #[test]
+    fn spritesheet_manifest() {
+        let dir = std::env::temp_dir();
+        let image_path = dir.join(format!("wifi-qr-sprites-{}.png", std::process::id()));
+        let manifest_path = dir.join(format!("wifi-qr-sprites-{}.json", std::process::id()));
+        let networks =
+            ["Lobby", "Say \"hi\"", "back\\slash"].map(|ssid| Wifi::new(ssid.to_string()));
+
+        generate_spritesheet(&networks, None, &image_path, &manifest_path).unwrap();
+        let manifest = std::fs::read_to_string(&manifest_path).unwrap();
+        std::fs::remove_file(&image_path).unwrap();
+        std::fs::remove_file(&manifest_path).unwrap();
+
+        assert_eq!(manifest.matches(r#""index":"#).count(), networks.len());
+        assert!(manifest.contains(r#""index":0,"ssid":"Lobby","x":0,"#));
+        assert!(manifest.contains(r#""ssid":"Say \"hi\"""#));
+        assert!(manifest.contains(r#""ssid":"back\\slash""#));
+    }
+
+    #[test]
+    fn scan_guide() {
+        let wifi = Wifi::new("Test".to_string());
+        let plain = wifi.generate_rgba_image().unwrap();
+        let guided = wifi.with_scan_guide(true).generate_rgba_image().unwrap();
+        let width = plain.width();
+
+        // one module (8 pixels) in from each corner
+        let far = width - 9;
+        for (x, y) in [(8, 8), (far, 8), (8, far), (far, far)] {
+            assert_eq!(plain.get_pixel(x, y).0, [255; 4]);
+            assert_eq!(guided.get_pixel(x, y).0, [0, 0, 0, 255], "{x},{y}");
+        }
+        // the modules within the 4 module quiet zone are untouched
+        let code = 32..width - 32;
+        for (x, y, pixel) in guided.enumerate_pixels() {
+            if code.contains(&x) && code.contains(&y) {
+                assert_eq!(plain.get_pixel(x, y), pixel, "{x},{y}");
+            }
+        }
+    }
+
+    #[test]
+    fn counter_file_names() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let wifi = Wifi::new("Lobby".to_string())
+            .with_file_name_suffix(FileNameSuffix::Counter(counter.clone()));
+
+        let names = std::thread::scope(|scope| {
+            let threads = [(); 2].map(|()| {
+                let wifi = wifi.clone();
+                scope.spawn(move || {
+                    (0..50)
+                        .map(|_| wifi.file_name(None, &ImageFormat::png()))
+                        .collect::<Vec<_>>()
+                })
+            });
+            threads.map(|thread| thread.join().unwrap()).concat()
+        });
+
+        let unique = names.iter().collect::<std::collections::HashSet<_>>();
+        assert_eq!(unique.len(), 100);
+        assert!(names.iter().all(|name| name.starts_with("wifi-Lobby-")));
+        assert_eq!(counter.load(Ordering::Relaxed), 100);
+    }
+
+    #[cfg(feature = "print")]
+    #[test]
+    fn print_dimensions() {
+        // 50mm at 300 DPI are 590.55 pixels
+        let wifi = Wifi::new("Test".to_string()).for_print(50.0, 300);
+        assert_eq!(wifi.generate_rgba_image().unwrap().dimensions(), (591, 591));
+
+        let bytes = wifi.generate_image_bytes(ImageFormat::png()).unwrap();
+        let reader = png::Decoder::new(bytes.as_slice()).read_info().unwrap();
+        let info = reader.info();
+        assert_eq!((info.width, info.height), (591, 591));
+        let dims = info.pixel_dims.unwrap();
+        assert_eq!((dims.xppu, dims.yppu), (11811, 11811));
+        assert_eq!(dims.unit, png::Unit::Meter);
+    }
+
+    #[test]
+    fn rounded_finders() {
+        let wifi = Wifi::new("Test".to_string()).with_ec_level(Some(qrcode::EcLevel::M));
+        let square = wifi.generate_rgba_image().unwrap();
+        let rounded = wifi
+            .with_rounded_finders(true)
+            .generate_rgba_image()
+            .unwrap();
+        let width = square.width();
+        let dark = [0, 0, 0, 255];
+
+        // the outermost pixel of each finder corner module is cleared, its center stays dark
+        let (near, far) = (32, width - 32 - 56);
+        for (left, top) in [(near, near), (far, near), (near, far)] {
+            for (x, y, center) in [
+                (left, top, (left + 4, top + 4)),
+                (left + 55, top, (left + 51, top + 4)),
+                (left, top + 55, (left + 4, top + 51)),
+                (left + 55, top + 55, (left + 51, top + 51)),
+            ] {
+                assert_eq!(square.get_pixel(x, y).0, dark);
+                assert_eq!(rounded.get_pixel(x, y).0, [255; 4], "{x},{y}");
+                assert_eq!(rounded.get_pixel(center.0, center.1).0, dark);
+            }
+        }
+
+        // nothing outside of the finder patterns changes
+        let finder = |x: u32, y: u32| {
+            let near = |v| (near..near + 56).contains(&v);
+            let far = |v| (far..far + 56).contains(&v);
+            (near(x) || far(x)) && (near(y) || far(y)) && !(far(x) && far(y))
+        };
+        for (x, y, pixel) in rounded.enumerate_pixels() {
+            if !finder(x, y) {
+                assert_eq!(square.get_pixel(x, y), pixel, "{x},{y}");
+            }
+        }
+    }
+
+    #[cfg(all(feature = "serde", any(feature = "image-codecs", feature = "png-only")))]
+    #[test]
+    fn batch_ndjson() {
+        use base64::Engine;
+
+        let networks = [
+            Wifi::new("Say \"hi\"".to_string()),
+            // too long for any code, this row fails without stopping the batch
+            Wifi::new("Too long".to_string()).with_raw_field("X".to_string(), "x".repeat(3000)),
+            Wifi::new("Home".to_string())
+                .with_method(Some(crate::WifiMethod::Wpa))
+                .with_password(Some("password".to_string())),
+        ];
+        let mut output = Vec::new();
+        let results = generate_batch_ndjson(&networks, ImageFormat::png(), true, &mut output);
+
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(GenerationError::QrError(_))));
+        assert!(results[2].is_ok());
+
+        let lines = String::from_utf8(output).unwrap();
+        let lines = lines
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["ssid"], "Say \"hi\"");
+        assert_eq!(lines[0].get("password"), None);
+        assert_eq!(lines[1]["ssid"], "Home");
+        assert_eq!(lines[1]["password"], "password");
+        for line in &lines {
+            let image = base64::engine::general_purpose::STANDARD
+                .decode(line["image"].as_str().unwrap())
+                .unwrap();
+            assert!(image::load_from_memory(&image).is_ok());
+        }
+    }
+
+    #[cfg(feature = "image-codecs")]
+    #[test]
+    fn web_render() {
+        let wifi = Wifi::new("Lobby".to_string());
+        for name in ["png", "gif", "bmp"] {
+            let content_type = format!("image/{name}");
+            let format = ImageFormat::from_name(name).unwrap();
+            let web = wifi.render_web(format).unwrap();
+            assert_eq!(web.content_type, content_type);
+            assert_eq!(web.filename, format!("wifi-Lobby.{name}"));
+            assert_eq!(web.report.modules, 21);
+
+            let decoded = image::guess_format(&web.bytes).unwrap();
+            assert_eq!(decoded.to_mime_type(), content_type);
+            assert!(image::load_from_memory(&web.bytes).is_ok());
+        }
+    }
+
+    #[cfg(all(feature = "webp", feature = "image-codecs"))]
+    #[test]
+    fn webp_file() {
+        let path = std::env::temp_dir().join(format!("wifi-qr-{}.webp", std::process::id()));
+        let wifi = Wifi::new("Test".to_string());
+        wifi.generate_image_file(None, &path).unwrap();
+        let image = image::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // lossless, so the pixels survive unchanged
+        let expected = wifi.render().unwrap().into_rgba().buffer;
+        assert_eq!(image.to_rgba8(), expected);
+    }
+
+    #[cfg(any(feature = "image-codecs", feature = "png-only"))]
+    #[test]
+    fn data_uri() {
+        use base64::Engine;
+
+        let wifi = Wifi::new("Test".to_string());
+        let uri = wifi.generate_data_uri(ImageFormat::png()).unwrap();
+        let data = uri.strip_prefix("data:image/png;base64,").unwrap();
+
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .unwrap();
+        assert_eq!(
+            bytes,
+            wifi.generate_image_bytes(ImageFormat::png()).unwrap()
+        );
+    }
+
+    #[cfg(feature = "verify")]
+    #[test]
+    fn verified_file() {
+        let path =
+            std::env::temp_dir().join(format!("wifi-qr-verified-{}.png", std::process::id()));
+        let wifi = Wifi::new("Home".to_string())
+            .with_method(Some(crate::WifiMethod::Wpa))
+            .with_password(Some("password".to_string()));
+
+        // too little contrast to scan, nothing gets written
+        let faint = wifi.clone().with_colors([250, 250, 250, 255], [255; 4]);
+        assert!(matches!(
+            faint.generate_image_file_verified(Some(ImageFormat::png()), &path),
+            Err(GenerationError::VerificationFailed(_))
+        ));
+        assert!(!path.exists());
+
+        wifi.generate_image_file_verified(Some(ImageFormat::png()), &path)
+            .unwrap();
+        assert!(path.exists());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "image-codecs")]
+    #[test]
+    fn format_from_extension() {
+        for (extension, content_type) in [
+            ("png", Some("image/png")),
+            ("PNG", Some("image/png")),
+            ("jpg", Some("image/jpeg")),
+            ("jpeg", Some("image/jpeg")),
+            ("JpG", Some("image/jpeg")),
+            ("bmp", Some("image/bmp")),
+            ("Gif", Some("image/gif")),
+            ("svg", None),
+            ("tiff", None),
+            ("", None),
+        ] {
+            assert_eq!(
+                ImageFormat::from_extension(extension).map(|format| format.content_type()),
+                content_type,
+                "{extension}"
+            );
+        }
+    }
+
+    #[test]
+    fn logo_placement() {
+        const BLUE: [u8; 4] = [0, 0, 255, 255];
+        let logo = DynamicImage::ImageRgba8(ImageBuffer::from_pixel(10, 10, Rgba(BLUE)));
+
+        // 20% of the 168 pixel wide code, centered in the 232 pixel wide image
+        let (left, top, placed) = place_logo(&logo, 0.5, 168, (232, 232), false).unwrap();
+        assert_eq!(placed.dimensions(), (33, 33));
+        assert_eq!((left, top), (99, 99));
+        assert!(place_logo(&logo, 0.0, 168, (232, 232), false).is_none());
+
+        // rendered, only the pixels under the logo change
+        let wifi = Wifi::new("Test".to_string()).with_ec_level(Some(qrcode::EcLevel::H));
+        let plain = wifi.generate_rgba_image().unwrap();
+        let branded = wifi
+            .with_logo(logo.clone(), 0.5)
+            .generate_rgba_image()
+            .unwrap();
+        // the code is the image without the quiet zone, 4 modules of 8 pixels on each side
+        let (width, _) = plain.dimensions();
+        let (left, top, placed) =
+            place_logo(&logo, 0.5, width - 64, (width, width), false).unwrap();
+        let logo_area = |x, y| {
+            (left..left + placed.width()).contains(&x) && (top..top + placed.height()).contains(&y)
+        };
+        for (x, y, pixel) in branded.enumerate_pixels() {
+            if logo_area(x, y) {
+                assert_eq!(pixel.0, BLUE, "{x},{y}");
+            } else {
+                assert_eq!(plain.get_pixel(x, y), pixel, "{x},{y}");
+            }
+        }
+    }
+
+    #[test]
+    fn caption_below_code() {
+        // the caption raises the error correction level, keep it the same for comparison
+        let wifi = Wifi::new("Guest".to_string()).with_ec_level(Some(qrcode::EcLevel::Q));
+        let plain = wifi.generate_rgba_image().unwrap();
+        let captioned = wifi
+            .with_caption(Some("Guest".to_string()))
+            .generate_rgba_image()
+            .unwrap();
+
+        assert_eq!(captioned.width(), plain.width());
+        assert!(captioned.height() > plain.height());
+        for (x, y, pixel) in plain.enumerate_pixels() {
+            assert_eq!(captioned.get_pixel(x, y), pixel, "{x},{y}");
+        }
+        // the text is drawn into the added rows
+        assert!((plain.height()..captioned.height())
+            .any(|y| (0..plain.width()).any(|x| captioned.get_pixel(x, y).0 == [0, 0, 0, 255])));
+    }
+
+    #[test]
+    fn inverted() {
+        let wifi = Wifi::new("Test".to_string());
+        let normal = wifi.generate_rgba_image().unwrap();
+        let inverted = wifi.with_inverted(true).generate_rgba_image().unwrap();
+
+        // the corner of the top left finder pattern and the quiet zone swap colors
+        assert_eq!(normal.get_pixel(32, 32).0, [0, 0, 0, 255]);
+        assert_eq!(inverted.get_pixel(32, 32).0, [255; 4]);
+        assert_eq!(normal.get_pixel(0, 0).0, [255; 4]);
+        assert_eq!(inverted.get_pixel(0, 0).0, [0, 0, 0, 255]);
+    }
+
+    #[cfg(all(feature = "qoi", any(feature = "image-codecs", feature = "png-only")))]
+    #[test]
+    fn written_format() {
+        let wifi = Wifi::new("Test".to_string());
+        let name = format!("wifi-qr-written-{}", std::process::id());
+
+        for (extension, expected) in [("qoi", "Qoi"), ("png", "Png"), ("QOI", "Qoi")] {
+            let path = std::env::temp_dir().join(format!("{name}.{extension}"));
+            let format = wifi.generate_image_file(None, &path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(format!("{format:?}"), expected);
+        }
+    }
+
+    #[cfg(feature = "image-codecs")]
+    #[test]
+    fn jpeg_quality() {
+        let wifi = Wifi::new("Test".to_string());
+        let jpeg = |quality| {
+            wifi.clone()
+                .with_jpeg_options(Some(JpegOptions { quality }))
+                .generate_image_bytes(ImageFormat::from_name("jpeg").unwrap())
+                .unwrap()
+        };
+
+        let (low, high) = (jpeg(10), jpeg(95));
+        assert!(high.len() > low.len(), "{} <= {}", high.len(), low.len());
+        assert!(image::load_from_memory(&low).is_ok());
+    }
+
+    #[cfg(feature = "png-only")]
+    #[test]
+    fn png_compression() {
+        let wifi = Wifi::new("Test".to_string());
+        let png = |compression| {
+            let options = PngOptions {
+                compression,
+                filter: PngFilter::NoFilter,
+            };
+            wifi.clone()
+                .with_png_options(Some(options))
+                .generate_image_bytes(ImageFormat::png())
+                .unwrap()
+        };
+
+        let (fast, best) = (png(PngCompression::Fast), png(PngCompression::Best));
+        assert!(best.len() < fast.len(), "{} >= {}", best.len(), fast.len());
+        // only the size differs, not the pixels
+        let decode = |bytes: &[u8]| {
+            let mut reader = png::Decoder::new(bytes).read_info().unwrap();
+            let mut pixels = vec![0; reader.output_buffer_size()];
+            reader.next_frame(&mut pixels).unwrap();
+            pixels
+        };
+        assert_eq!(decode(&fast), decode(&best));
+    }
+
+    #[cfg(all(feature = "tokio", any(feature = "image-codecs", feature = "png-only")))]
+    #[test]
+    fn image_file_async() {
+        let path = std::env::temp_dir().join(format!("wifi-qr-async-{}.png", std::process::id()));
+        let wifi = Wifi::new("Test".to_string());
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let format = runtime
+            .block_on(wifi.generate_image_file_async(None, &path))
+            .unwrap();
+        let written = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(format!("{format:?}"), "Png");
+        assert_eq!(
+            written,
+            wifi.generate_image_bytes(ImageFormat::png()).unwrap()
+        );
+    }
+
+    #[test]
+    fn raster_border() {
+        let width = |wifi: Wifi| wifi.generate_rgba_image().unwrap().width();
+        let wifi = Wifi::new("Test".to_string());
+
+        // 21 modules of 8 pixels, with 4 modules on each side by default
+        assert_eq!(width(wifi.clone()), (21 + 8) * 8);
+        assert_eq!(width(wifi.clone().with_border(Some(1))), (21 + 2) * 8);
+        assert_eq!(width(wifi.clone().with_border(Some(10))), (21 + 20) * 8);
+        assert_eq!(width(wifi.clone().with_quiet_zone(false)), 21 * 8);
+
+        // the corner of the top left finder pattern moves with the border
+        let image = wifi.with_border(Some(1)).generate_rgba_image().unwrap();
+        assert_eq!(image.get_pixel(7, 7).0, [255; 4]);
+        assert_eq!(image.get_pixel(8, 8).0, [0, 0, 0, 255]);
+    }
+}
@@ -0,0 +1,101 @@
+//! Readable representations of the binary fields of [`crate::Wifi`]
+
+use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+pub(crate) fn default_true() -> bool {
+    true
+}
+
+/// The public key as base64, like in the `K` field of the payload
+pub(crate) mod public_key {
+    use base64::Engine;
+
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        value: &Option<Vec<u8>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(bytes) => {
+                serializer.serialize_some(&base64::engine::general_purpose::STANDARD.encode(bytes))
+            }
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Vec<u8>>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|value| base64::engine::general_purpose::STANDARD.decode(value))
+            .transpose()
+            .map_err(D::Error::custom)
+    }
+}
+
+/// The PSK as 64 hex digits, like in the `P` field of the payload
+pub(crate) mod psk {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        value: &Option<[u8; 32]>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(bytes) => serializer
+                .serialize_some(&bytes.iter().map(|b| format!("{b:02x}")).collect::<String>()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<[u8; 32]>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|value| {
+                crate::parse::parse_psk(&value)
+                    .ok_or_else(|| D::Error::custom("expected a PSK of 64 hex digits"))
+            })
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{EapMethod, Phase2, Wifi, WifiMethod};
+
+    #[test]
+    fn round_trip() {
+        let wifi = Wifi::new("Office".to_string())
+            .with_method(Some(WifiMethod::Wpa2Enterprise))
+            .with_hidden(true)
+            .with_eap_method(Some(EapMethod::Tls))
+            .with_phase2(Some(Phase2::MsChapV2))
+            .with_anonymous_identity(Some("anonymous".to_string()))
+            .with_identity(Some("user".to_string()))
+            .with_password(Some("password".to_string()))
+            .with_public_key(Some(b"public key".to_vec()))
+            .with_raw_field("O".to_string(), "vendor".to_string());
+
+        let json = serde_json::to_value(&wifi).unwrap();
+        assert_eq!(json["kind"], "wpa2-enterprise");
+        assert_eq!(json["phase2"], "ms-chap-v2");
+        assert_eq!(json["public_key"], "cHVibGljIGtleQ==");
+        assert_eq!(serde_json::from_value::<Wifi>(json).unwrap(), wifi);
+
+        let wifi = Wifi::new("Home".to_string())
+            .with_method(Some(WifiMethod::Wpa))
+            .with_psk_hex([0xab; 32]);
+        let json = serde_json::to_value(&wifi).unwrap();
+        assert_eq!(json["psk"], "ab".repeat(32));
+        assert_eq!(serde_json::from_value::<Wifi>(json).unwrap(), wifi);
+    }
+
+    #[test]
+    fn defaults() {
+        let wifi: Wifi = serde_json::from_str(r#"{"ssid":"Test","kind":"wpa3"}"#).unwrap();
+        assert_eq!(wifi.payload(), "WIFI:T:WPA;R:1;S:Test;;");
+        assert!(serde_json::from_str::<Wifi>(r#"{"ssid":"Test","psk":"abc"}"#).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+use std::path::Path;
+
+use qrcode::render::svg;
+
+use crate::{GenerationError, Wifi};
+
+impl Wifi {
+    /// Add the payload as a `<desc>` element to generated SVGs,
+    /// so screen readers and other tools can read it without decoding the code.
+    ///
+    /// Off by default, as anyone with the file could read the password from it.
+    pub fn with_embed_payload_metadata(mut self, embed: bool) -> Self {
+        self.render_options.embed_payload_metadata = embed;
+        self
+    }
+
+    /// Replace the password in the embedded payload with `****`, see [`Wifi::to_redacted_string`]
+    pub fn with_redacted_payload_metadata(mut self, redact: bool) -> Self {
+        self.render_options.redact_payload_metadata = redact;
+        self
+    }
+
+    /// Render the QR-Code as an SVG document
+    pub fn generate_svg_string(&self) -> Result<String, GenerationError> {
+        let (dark, [r, g, b, a]) = self.render_options.colors();
+        let dark = css_color(dark);
+        let alpha = if self.render_options.transparent_background {
+            0
+        } else {
+            a
+        };
+        let light = css_color([r, g, b, alpha]);
+        let code = self.qr_code()?;
+        let colors = code.to_colors();
+        let svg = self
+            .renderer(&colors, code.width())
+            .dark_color(svg::Color(&dark))
+            .light_color(svg::Color(&light))
+            .build();
+
+        if !self.render_options.embed_payload_metadata {
+            return Ok(svg);
+        }
+
+        let payload = if self.render_options.redact_payload_metadata {
+            self.to_redacted_string()
+        } else {
+            self.payload()
+        };
+
+        // insert as the first child of the root element
+        let root_end = svg
+            .find("<svg")
+            .and_then(|start| svg[start..].find('>').map(|end| start + end + 1))
+            .expect("qrcode renders an svg root element");
+        let mut document = String::with_capacity(svg.len() + payload.len() + 13);
+        document.push_str(&svg[..root_end]);
+        document.push_str("<desc>");
+        document.push_str(&xml_escape(&payload));
+        document.push_str("</desc>");
+        document.push_str(&svg[root_end..]);
+        Ok(document)
+    }
+
+    pub fn generate_svg_file(&self, file_path: &Path) -> Result<(), GenerationError> {
+        std::fs::write(file_path, self.generate_svg_string()?)?;
+        Ok(())
+    }
+}
+
+/// `#rrggbb`, or `#rrggbbaa` if not opaque
+fn css_color([r, g, b, a]: [u8; 4]) -> String {
+    if a == 255 {
+        format!("#{r:02x}{g:02x}{b:02x}")
+    } else {
+        format!("#{r:02x}{g:02x}{b:02x}{a:02x}")
+    }
+}
+
+fn xml_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn payload_metadata() {
+        let wifi = Wifi::new("Tom & Jerry".to_string())
+            .with_method(Some(crate::WifiMethod::Wpa))
+            .with_password(Some("<secret>".to_string()));
+        assert!(!wifi.generate_svg_string().unwrap().contains("<desc>"));
+
+        let wifi = wifi.with_embed_payload_metadata(true);
+        let svg = wifi.generate_svg_string().unwrap();
+        assert!(svg.contains("<desc>WIFI:T:WPA;S:Tom &amp; Jerry;P:****;;</desc>"));
+        assert!(!svg.contains("secret"));
+
+        let svg = wifi
+            .with_redacted_payload_metadata(false)
+            .generate_svg_string()
+            .unwrap();
+        assert!(svg.contains("<desc>WIFI:T:WPA;S:Tom &amp; Jerry;P:&lt;secret&gt;;;</desc>"));
+        // the description is the first child of the root element
+        let root = svg.find("<svg").unwrap();
+        let root_end = root + svg[root..].find('>').unwrap() + 1;
+        assert!(svg[root_end..].starts_with("<desc>"));
+    }
+
+    #[test]
+    fn svg_document() {
+        let wifi = Wifi::new("Test".to_string());
+        let svg = wifi.generate_svg_string().unwrap();
+        assert!(svg.starts_with("<?xml") || svg.starts_with("<svg"));
+        assert!(svg.contains("<rect"));
+        assert!(svg.contains("#000000") && svg.contains("#ffffff"));
+
+        let svg = wifi
+            .with_colors([0x12, 0x34, 0x56, 255], [255, 255, 255, 0x80])
+            .generate_svg_string()
+            .unwrap();
+        assert!(svg.contains("#123456") && svg.contains("#ffffff80"));
+    }
+
+    #[test]
+    fn svg_border() {
+        let size = |wifi: Wifi| {
+            let svg = wifi.generate_svg_string().unwrap();
+            let start = svg.find("width=\"").unwrap() + "width=\"".len();
+            let end = start + svg[start..].find('"').unwrap();
+            svg[start..end].parse::<u32>().unwrap()
+        };
+        let wifi = Wifi::new("Test".to_string()).with_module_size(1);
+        let code_width = size(wifi.clone().with_quiet_zone(false));
+        assert_eq!(size(wifi.clone()), code_width + 8);
+        assert_eq!(size(wifi.with_border(Some(2))), code_width + 4);
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{GenerationError, Wifi};
+
+impl Wifi {
+    /// Render the QR-Code as text for printing to a terminal,
+    /// each module is two characters wide, `██` for dark and spaces for light ones
+    pub fn generate_terminal_string(&self) -> Result<String, GenerationError> {
+        let (modules, width) = self.terminal_modules()?;
+
+        let mut text = String::with_capacity(modules.len() * "██".len() + width);
+        for row in modules.chunks(width) {
+            for &dark in row {
+                text.push_str(if dark { "██" } else { "  " });
+            }
+            text.push('\n');
+        }
+        Ok(text)
+    }
+
+    /// Like [`Wifi::generate_terminal_string`], but each character covers two rows of modules
+    /// using half blocks, for a code that is half as tall
+    pub fn generate_terminal_halfblock(&self) -> Result<String, GenerationError> {
+        let (modules, width) = self.terminal_modules()?;
+
+        let mut text = String::with_capacity(modules.len() / 2 * "█".len() + width);
+        for rows in modules.chunks(2 * width) {
+            let (top, bottom) = rows.split_at(width);
+            for (x, &top) in top.iter().enumerate() {
+                // a missing last row is part of the light border
+                let bottom = bottom.get(x).copied().unwrap_or(false);
+                text.push(match (top, bottom) {
+                    (true, true) => '█',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (false, false) => ' ',
+                });
+            }
+            text.push('\n');
+        }
+        Ok(text)
+    }
+
+    /// The modules of the code including the quiet zone, `true` for dark ones,
+    /// together with the width of a row
+    fn terminal_modules(&self) -> Result<(Vec<bool>, usize), GenerationError> {
+        let matrix = self.generate_matrix()?;
+        let quiet_zone = self.render_options.quiet_zone_width() as usize;
+        let width = matrix.len() + 2 * quiet_zone;
+
+        let mut modules = vec![false; width * width];
+        for (y, row) in matrix.into_iter().enumerate() {
+            let start = (y + quiet_zone) * width + quiet_zone;
+            modules[start..start + row.len()].copy_from_slice(&row);
+        }
+        Ok((modules, width))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dark_modules(wifi: &Wifi) -> usize {
+        let matrix = wifi.generate_matrix().unwrap();
+        matrix.iter().flatten().filter(|&&dark| dark).count()
+    }
+
+    #[test]
+    fn terminal_string() {
+        let wifi = Wifi::new("Test".to_string());
+        let text = wifi.generate_terminal_string().unwrap();
+
+        // 21 modules and a quiet zone of 4 on each side
+        assert_eq!(text.lines().count(), 29);
+        assert!(text.lines().all(|line| line.chars().count() == 2 * 29));
+        assert_eq!(text.matches("██").count(), dark_modules(&wifi));
+        assert!(text.lines().next().unwrap().trim().is_empty());
+    }
+
+    #[test]
+    fn terminal_halfblock() {
+        let wifi = Wifi::new("Test".to_string());
+        let text = wifi.generate_terminal_halfblock().unwrap();
+
+        // two of the 29 rows of modules per line, the last line only has one
+        assert_eq!(text.lines().count(), 15);
+        assert!(text.lines().all(|line| line.chars().count() == 29));
+        let halves = text
+            .chars()
+            .map(|c| match c {
+                '█' => 2,
+                '▀' | '▄' => 1,
+                _ => 0,
+            })
+            .sum::<usize>();
+        assert_eq!(halves, dark_modules(&wifi));
+    }
+}
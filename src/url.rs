@@ -0,0 +1,155 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{EapMethod, GenerationError, Phase2, Wifi, WifiMethod};
+
+const PREFIX: &str = "wifiqr://generate";
+
+impl Wifi {
+    /// Build a network from a `wifiqr://generate?ssid=...` link
+    ///
+    /// Recognized query parameters are `ssid` (required), `type` (e.g. `wpa`, `wep`, `nopass`),
+    /// `pw`/`password`, `hidden`, `eap`, `ph2`, `identity` and `anon`,
+    /// values are percent-decoded and `+` is read as a space.
+    pub fn from_url(url: &str) -> Result<Self, GenerationError> {
+        let invalid = |reason: &str| GenerationError::InvalidUrl(format!("{reason}: {url}"));
+
+        let query = url
+            .strip_prefix(PREFIX)
+            .ok_or_else(|| invalid("expected a wifiqr://generate link"))?;
+        let query = match query.strip_prefix('/').unwrap_or(query) {
+            "" => "",
+            query => query
+                .strip_prefix('?')
+                .ok_or_else(|| invalid("expected a query after wifiqr://generate"))?,
+        };
+
+        let mut ssid = None;
+        let mut wifi = Wifi::new(String::new());
+
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value =
+                percent_decode(value).ok_or_else(|| invalid("malformed percent encoding"))?;
+
+            match key {
+                "ssid" => ssid = Some(value),
+                "type" => {
+                    wifi.kind = Some(parse_method(&value).ok_or_else(|| invalid("unknown type"))?)
+                }
+                "pw" | "password" => wifi.password = Some(value),
+                "hidden" => wifi.hidden = matches!(value.as_str(), "" | "1" | "true"),
+                "eap" => {
+                    wifi.eap_method = Some(parse_eap(&value).ok_or_else(|| invalid("unknown eap"))?)
+                }
+                "ph2" => {
+                    wifi.phase2 = Some(parse_phase2(&value).ok_or_else(|| invalid("unknown ph2"))?)
+                }
+                "identity" => wifi.identity = Some(value),
+                "anon" => wifi.anonymous_identity = Some(value),
+                _ => return Err(invalid(&format!("unknown parameter `{key}`"))),
+            }
+        }
+
+        wifi.ssid = ssid.ok_or_else(|| invalid("missing ssid"))?;
+        Ok(wifi)
+    }
+}
+
+fn parse_method(value: &str) -> Option<WifiMethod> {
+    Some(match value.to_ascii_lowercase().as_str() {
+        "nopass" | "open" => WifiMethod::NoPass,
+        "wep" => WifiMethod::Wep,
+        "wpa" => WifiMethod::Wpa,
+        "wpa2" | "wpa2-personal" => WifiMethod::Wpa2,
+        "wpa2-eap" | "wpa2-enterprise" => WifiMethod::Wpa2Enterprise,
+        "wpa3" => WifiMethod::Wpa3,
+        "owe" => WifiMethod::Owe,
+        _ => return None,
+    })
+}
+
+pub(crate) fn parse_eap(value: &str) -> Option<EapMethod> {
+    Some(match value.to_ascii_uppercase().as_str() {
+        "PEAP" => EapMethod::Peap,
+        "TLS" => EapMethod::Tls,
+        "TTLS" => EapMethod::Ttls,
+        "PWD" => EapMethod::Pwd,
+        "SIM" => EapMethod::Sim,
+        "AKA" => EapMethod::Aka,
+        "AKA_PRIME" => EapMethod::AkaPrime,
+        _ => return None,
+    })
+}
+
+pub(crate) fn parse_phase2(value: &str) -> Option<Phase2> {
+    Some(match value.to_ascii_uppercase().as_str() {
+        "MSCHAP" => Phase2::MsChap,
+        "MSCHAPV2" => Phase2::MsChapV2,
+        "PAP" => Phase2::Pap,
+        "GTC" => Phase2::Gtc,
+        "SIM" => Phase2::Sim,
+        "AKA" => Phase2::Aka,
+        "AKA_PRIME" => Phase2::AkaPrime,
+        _ => return None,
+    })
+}
+
+/// Decode `%XX` escapes and `+`, `None` if an escape is malformed or the result isn't UTF-8
+fn percent_decode(value: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut input = value.bytes();
+
+    while let Some(byte) = input.next() {
+        match byte {
+            b'%' => {
+                let high = (input.next()? as char).to_digit(16)?;
+                let low = (input.next()? as char).to_digit(16)?;
+                bytes.push((high * 16 + low) as u8);
+            }
+            b'+' => bytes.push(b' '),
+            byte => bytes.push(byte),
+        }
+    }
+
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_values() {
+        let wifi = Wifi::from_url(
+            "wifiqr://generate?ssid=Caf%C3%A9+Guest&type=WPA2&pw=p%40ss+word&hidden=1",
+        )
+        .unwrap();
+        assert_eq!(wifi.ssid, "Café Guest");
+        assert_eq!(wifi.kind, Some(WifiMethod::Wpa2));
+        assert_eq!(wifi.password.as_deref(), Some("p@ss word"));
+        assert!(wifi.hidden);
+
+        let wifi = Wifi::from_url("wifiqr://generate/?ssid=Office&eap=peap&ph2=mschapv2").unwrap();
+        assert_eq!(wifi.eap_method, Some(EapMethod::Peap));
+        assert_eq!(wifi.phase2, Some(Phase2::MsChapV2));
+    }
+
+    #[test]
+    fn rejects_invalid_links() {
+        let invalid = |url| matches!(Wifi::from_url(url), Err(GenerationError::InvalidUrl(_)));
+
+        // the ssid is required
+        assert!(invalid("wifiqr://generate"));
+        assert!(invalid("wifiqr://generate?type=wpa&pw=password"));
+        assert!(invalid("https://example.com/?ssid=Test"));
+        // malformed escapes, also when they don't decode to UTF-8
+        assert!(invalid("wifiqr://generate?ssid=100%"));
+        assert!(invalid("wifiqr://generate?ssid=%zz"));
+        assert!(invalid("wifiqr://generate?ssid=%ff"));
+        // unknown parameters and values
+        assert!(invalid("wifiqr://generate?ssid=Test&channel=6"));
+        assert!(invalid("wifiqr://generate?ssid=Test&type=wpa4"));
+    }
+}
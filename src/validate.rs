@@ -0,0 +1,466 @@
+use alloc::vec::Vec;
+
+use crate::{EapMethod, Wifi, WifiMethod};
+
+/// Why a network would produce a code devices reject, see [`Wifi::validate`]
+///
+/// Each variant names the field at fault, so a form can point at the right input.
+/// Passwords are never included, only their length.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum ValidationError {
+    #[error("the SSID is empty")]
+    SsidEmpty,
+    #[error("the SSID has to be at most 32 bytes long, not {len}")]
+    SsidTooLong { len: usize },
+    #[error("`{field}` contains a control character like a newline or tab")]
+    ControlCharacter { field: &'static str },
+    #[error("the network needs a password")]
+    MissingPassword,
+    #[error("open networks don't use a password")]
+    UnexpectedPassword,
+    #[error("a WPA passphrase may only contain printable ASCII characters")]
+    PasswordNotAscii,
+    #[error("a WPA passphrase has to be at least 8 characters long, not {len}")]
+    PasswordTooShort { len: usize },
+    #[error("a WPA passphrase has to be at most 63 characters long, not {len}")]
+    PasswordTooLong { len: usize },
+    #[error("a WEP key has to be 5 or 13 characters or 10 or 26 hex digits, not {len}")]
+    WepKeyInvalid { len: usize },
+    #[error("a raw PSK can only be used with WPA")]
+    PskWithoutWpa,
+    #[error("either a password or a raw PSK can be used, not both")]
+    PasswordAndPsk,
+    #[error("`{field}` is only used by WPA2-Enterprise networks")]
+    EnterpriseFieldWithoutEnterpriseMethod { field: &'static str },
+    #[error("WPA2-Enterprise networks need an EAP method")]
+    MissingEapMethod,
+    #[error("EAP method {eap:?} needs an identity")]
+    MissingIdentity { eap: EapMethod },
+    #[error("EAP method {eap:?} needs a public key")]
+    MissingPublicKey { eap: EapMethod },
+}
+
+impl Wifi {
+    /// Check the network against the rules of its method, e.g. the length of a WPA passphrase
+    /// or the identity an EAP method needs.
+    ///
+    /// Networks with a raw auth type (see [`Wifi::with_raw_auth_type`]) only get their SSID checked.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.ssid.is_empty() {
+            return Err(ValidationError::SsidEmpty);
+        }
+        if self.ssid.len() > 32 {
+            return Err(ValidationError::SsidTooLong {
+                len: self.ssid.len(),
+            });
+        }
+
+        // the payload has no escape for them, scanners would split the field or drop them
+        let fields = [
+            ("ssid", Some(&self.ssid)),
+            ("raw_auth_type", self.raw_auth_type.as_ref()),
+            ("anonymous_identity", self.anonymous_identity.as_ref()),
+            ("identity", self.identity.as_ref()),
+            ("password", self.password.as_ref()),
+        ];
+        let raw_fields = self
+            .raw_fields
+            .iter()
+            .flat_map(|(name, value)| [("raw_fields", Some(name)), ("raw_fields", Some(value))]);
+        for (field, value) in fields.into_iter().chain(raw_fields) {
+            if value.is_some_and(|value| value.chars().any(char::is_control)) {
+                return Err(ValidationError::ControlCharacter { field });
+            }
+        }
+
+        if self.raw_auth_type.is_some() {
+            return Ok(());
+        }
+
+        if !self.is_enterprise() {
+            for (field, set) in [
+                ("eap_method", self.eap_method.is_some()),
+                ("phase2", self.phase2.is_some()),
+                ("anonymous_identity", self.anonymous_identity.is_some()),
+                ("identity", self.identity.is_some()),
+                ("public_key", self.public_key.is_some()),
+            ] {
+                if set {
+                    return Err(ValidationError::EnterpriseFieldWithoutEnterpriseMethod { field });
+                }
+            }
+        }
+
+        if self.password.is_some() && self.psk.is_some() {
+            return Err(ValidationError::PasswordAndPsk);
+        }
+        let has_password = self.password.is_some() || self.psk.is_some();
+
+        match self.kind {
+            None => {}
+            Some(WifiMethod::NoPass | WifiMethod::Owe) if has_password => {
+                return Err(ValidationError::UnexpectedPassword);
+            }
+            Some(WifiMethod::NoPass | WifiMethod::Owe) => {}
+            Some(WifiMethod::Wep | WifiMethod::Wpa2Enterprise) if self.psk.is_some() => {
+                return Err(ValidationError::PskWithoutWpa);
+            }
+            Some(WifiMethod::Wep) => {
+                let key = self
+                    .password
+                    .as_ref()
+                    .ok_or(ValidationError::MissingPassword)?;
+                let valid = match key.len() {
+                    5 | 13 => key.is_ascii(),
+                    10 | 26 => key.bytes().all(|b| b.is_ascii_hexdigit()),
+                    _ => false,
+                };
+                if !valid {
+                    return Err(ValidationError::WepKeyInvalid {
+                        len: key.chars().count(),
+                    });
+                }
+            }
+            Some(WifiMethod::Wpa | WifiMethod::Wpa2 | WifiMethod::Wpa3) => {
+                match (&self.password, &self.psk) {
+                    (_, Some(_)) => {}
+                    (Some(password), None) => validate_passphrase(password)?,
+                    (None, None) => return Err(ValidationError::MissingPassword),
+                }
+            }
+            Some(WifiMethod::Wpa2Enterprise) => {
+                let eap = self
+                    .eap_method
+                    .as_ref()
+                    .ok_or(ValidationError::MissingEapMethod)?;
+                let (identity, password, public_key) = eap_requirements(eap);
+                if identity && self.identity.is_none() {
+                    return Err(ValidationError::MissingIdentity { eap: eap.clone() });
+                }
+                if password && self.password.is_none() {
+                    return Err(ValidationError::MissingPassword);
+                }
+                if public_key && self.public_key.is_none() {
+                    return Err(ValidationError::MissingPublicKey { eap: eap.clone() });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Wifi {
+    /// Finish building the network, failing if it doesn't pass [`Wifi::validate`]
+    pub fn build(self) -> Result<ValidWifi, ValidationError> {
+        self.validate()?;
+        Ok(ValidWifi(self))
+    }
+}
+
+/// A network that passed [`Wifi::validate`], see [`Wifi::build`]
+///
+/// Derefs to the [`Wifi`] for generating the code, it can't be changed anymore.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ValidWifi(Wifi);
+
+impl ValidWifi {
+    pub fn into_inner(self) -> Wifi {
+        self.0
+    }
+}
+
+impl core::ops::Deref for ValidWifi {
+    type Target = Wifi;
+
+    fn deref(&self) -> &Wifi {
+        &self.0
+    }
+}
+
+impl TryFrom<Wifi> for ValidWifi {
+    type Error = ValidationError;
+
+    fn try_from(wifi: Wifi) -> Result<Self, ValidationError> {
+        wifi.build()
+    }
+}
+
+/// Validate all networks up front, e.g. before rendering a large batch,
+/// returns the index and error of every invalid network
+pub fn validate_batch(networks: &[Wifi]) -> Vec<(usize, ValidationError)> {
+    networks
+        .iter()
+        .enumerate()
+        .filter_map(|(index, wifi)| wifi.validate().err().map(|err| (index, err)))
+        .collect()
+}
+
+fn validate_passphrase(password: &str) -> Result<(), ValidationError> {
+    if !password.bytes().all(|b| (0x20..=0x7e).contains(&b)) {
+        return Err(ValidationError::PasswordNotAscii);
+    }
+    match password.len() {
+        len @ 0..=7 => Err(ValidationError::PasswordTooShort { len }),
+        len @ 64.. => Err(ValidationError::PasswordTooLong { len }),
+        _ => Ok(()),
+    }
+}
+
+/// Which of identity, password and public key an EAP method needs
+fn eap_requirements(eap: &EapMethod) -> (bool, bool, bool) {
+    match eap {
+        EapMethod::Peap | EapMethod::Ttls | EapMethod::Pwd => (true, true, false),
+        EapMethod::Tls => (true, false, true),
+        // the credentials come from the SIM card
+        EapMethod::Sim | EapMethod::Aka | EapMethod::AkaPrime => (false, false, false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+    use alloc::string::{String, ToString};
+
+    use super::*;
+
+    fn wpa(password: &str) -> Wifi {
+        Wifi::new("Test".to_string())
+            .with_method(Some(WifiMethod::Wpa))
+            .with_password(Some(password.to_string()))
+    }
+
+    fn error(wifi: Wifi) -> ValidationError {
+        wifi.validate().unwrap_err()
+    }
+
+    #[test]
+    fn ssid() {
+        assert_eq!(error(Wifi::new(String::new())), ValidationError::SsidEmpty);
+        assert_eq!(
+            error(Wifi::new("x".repeat(40))),
+            ValidationError::SsidTooLong { len: 40 }
+        );
+        assert_eq!(
+            error(Wifi::new("Line\nBreak".to_string())),
+            ValidationError::ControlCharacter { field: "ssid" }
+        );
+    }
+
+    #[test]
+    fn control_characters() {
+        assert_eq!(
+            error(wpa("pass\tword")),
+            ValidationError::ControlCharacter { field: "password" }
+        );
+        assert_eq!(
+            error(wpa("password").with_raw_field("X".to_string(), "a\rb".to_string())),
+            ValidationError::ControlCharacter {
+                field: "raw_fields"
+            }
+        );
+    }
+
+    #[test]
+    fn passwords() {
+        assert_eq!(
+            error(Wifi::new("Test".to_string()).with_method(Some(WifiMethod::Wpa))),
+            ValidationError::MissingPassword
+        );
+        assert_eq!(
+            error(wpa("password").with_method(Some(WifiMethod::NoPass))),
+            ValidationError::UnexpectedPassword
+        );
+        assert_eq!(error(wpa("pässword")), ValidationError::PasswordNotAscii);
+        assert_eq!(
+            error(wpa("short")),
+            ValidationError::PasswordTooShort { len: 5 }
+        );
+        assert_eq!(
+            error(wpa(&"x".repeat(64))),
+            ValidationError::PasswordTooLong { len: 64 }
+        );
+        assert_eq!(
+            error(wpa("abcd").with_method(Some(WifiMethod::Wep))),
+            ValidationError::WepKeyInvalid { len: 4 }
+        );
+        assert!(wpa("abcde")
+            .with_method(Some(WifiMethod::Wep))
+            .validate()
+            .is_ok());
+        assert!(wpa("password").validate().is_ok());
+    }
+
+    #[test]
+    fn raw_psk() {
+        let wifi = Wifi::new("Test".to_string()).with_psk_hex([0; 32]);
+        assert!(wifi
+            .clone()
+            .with_method(Some(WifiMethod::Wpa3))
+            .validate()
+            .is_ok());
+        assert_eq!(
+            error(wifi.with_method(Some(WifiMethod::Wep))),
+            ValidationError::PskWithoutWpa
+        );
+
+        let both = wpa("password").with_psk_hex([0; 32]);
+        assert_eq!(error(both.clone()), ValidationError::PasswordAndPsk);
+        assert!(both.with_password(None).validate().is_ok());
+    }
+
+    #[test]
+    fn enterprise_fields() {
+        assert_eq!(
+            error(wpa("password").with_identity(Some("user".to_string()))),
+            ValidationError::EnterpriseFieldWithoutEnterpriseMethod { field: "identity" }
+        );
+        assert_eq!(
+            error(wpa("password").with_method(Some(WifiMethod::Wpa2Enterprise))),
+            ValidationError::MissingEapMethod
+        );
+    }
+
+    #[test]
+    fn batch_reports_invalid_indices() {
+        let networks = [
+            wpa("password"),
+            wpa("short"),
+            Wifi::new("Open".to_string()).with_method(Some(WifiMethod::NoPass)),
+            Wifi::new(String::new()),
+            wpa("another password"),
+        ];
+        assert_eq!(
+            validate_batch(&networks),
+            [
+                (1, ValidationError::PasswordTooShort { len: 5 }),
+                (3, ValidationError::SsidEmpty),
+            ]
+        );
+        assert!(validate_batch(&networks[..1]).is_empty());
+    }
+
+    #[test]
+    fn eap_methods() {
+        let enterprise = |eap: EapMethod| {
+            Wifi::new("Test".to_string())
+                .with_method(Some(WifiMethod::Wpa2Enterprise))
+                .with_eap_method(Some(eap))
+        };
+        let identity = |wifi: Wifi| wifi.with_identity(Some("user".to_string()));
+        let password = |wifi: Wifi| wifi.with_password(Some("password".to_string()));
+
+        for eap in [EapMethod::Peap, EapMethod::Ttls, EapMethod::Pwd] {
+            assert_eq!(
+                error(password(enterprise(eap.clone()))),
+                ValidationError::MissingIdentity { eap: eap.clone() }
+            );
+            assert_eq!(
+                error(identity(enterprise(eap.clone()))),
+                ValidationError::MissingPassword
+            );
+            assert!(password(identity(enterprise(eap))).validate().is_ok());
+        }
+
+        assert_eq!(
+            error(enterprise(EapMethod::Tls)),
+            ValidationError::MissingIdentity {
+                eap: EapMethod::Tls
+            }
+        );
+        assert_eq!(
+            error(identity(enterprise(EapMethod::Tls))),
+            ValidationError::MissingPublicKey {
+                eap: EapMethod::Tls
+            }
+        );
+        let tls = identity(enterprise(EapMethod::Tls)).with_public_key(Some([1, 2, 3].to_vec()));
+        assert!(tls.validate().is_ok());
+
+        for eap in [EapMethod::Sim, EapMethod::Aka, EapMethod::AkaPrime] {
+            assert!(enterprise(eap).validate().is_ok());
+        }
+    }
+
+    #[test]
+    fn line_breaks_and_tabs() {
+        for c in ['\n', '\r', '\t'] {
+            let ssid = format!("Guest{c}Network");
+            assert_eq!(
+                error(Wifi::new(ssid)),
+                ValidationError::ControlCharacter { field: "ssid" },
+                "{c:?}"
+            );
+            assert_eq!(
+                error(
+                    wpa("password")
+                        .with_method(Some(WifiMethod::Wpa2Enterprise))
+                        .with_eap_method(Some(EapMethod::Peap))
+                        .with_identity(Some(format!("user{c}")))
+                ),
+                ValidationError::ControlCharacter { field: "identity" },
+                "{c:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn build() {
+        let rejected = [
+            (Wifi::new(String::new()), ValidationError::SsidEmpty),
+            (
+                Wifi::new("Test".to_string()).with_method(Some(WifiMethod::Wpa)),
+                ValidationError::MissingPassword,
+            ),
+            (
+                wpa("password").with_method(Some(WifiMethod::NoPass)),
+                ValidationError::UnexpectedPassword,
+            ),
+            (
+                wpa("password").with_method(Some(WifiMethod::Wpa2Enterprise)),
+                ValidationError::MissingEapMethod,
+            ),
+            (
+                wpa("password")
+                    .with_method(Some(WifiMethod::Wpa2Enterprise))
+                    .with_eap_method(Some(EapMethod::Peap)),
+                ValidationError::MissingIdentity {
+                    eap: EapMethod::Peap,
+                },
+            ),
+            (
+                wpa("password").with_anonymous_identity(Some("anonymous".to_string())),
+                ValidationError::EnterpriseFieldWithoutEnterpriseMethod {
+                    field: "anonymous_identity",
+                },
+            ),
+        ];
+        for (wifi, expected) in rejected {
+            assert_eq!(wifi.clone().build(), Err(expected.clone()));
+            assert_eq!(ValidWifi::try_from(wifi), Err(expected));
+        }
+
+        let valid = wpa("password").build().unwrap();
+        assert_eq!(valid.ssid(), "Test");
+        assert_eq!(valid.into_inner(), wpa("password"));
+    }
+
+    #[test]
+    fn ssid_byte_limit() {
+        assert!(Wifi::new("x".repeat(32)).validate().is_ok());
+        assert_eq!(
+            error(Wifi::new("x".repeat(33))),
+            ValidationError::SsidTooLong { len: 33 }
+        );
+
+        // 31 + 2 bytes for `é` is 33 bytes in 32 characters
+        let ssid = format!("{}é", "x".repeat(31));
+        assert_eq!(ssid.chars().count(), 32);
+        assert_eq!(
+            error(Wifi::new(ssid)),
+            ValidationError::SsidTooLong { len: 33 }
+        );
+        assert!(Wifi::new(format!("{}é", "x".repeat(30))).validate().is_ok());
+    }
+}
@@ -0,0 +1,51 @@
+use qrcode::Color;
+use wasm_bindgen::prelude::*;
+
+use crate::Wifi;
+
+/// The modules of a QR-Code, for drawing it on the JS side without the image stack
+#[wasm_bindgen]
+pub struct QrMatrix {
+    width: usize,
+    modules: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl QrMatrix {
+    /// width and height of the code in modules, without the quiet zone
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// row-major modules, 1 for dark and 0 for light
+    #[wasm_bindgen(getter)]
+    pub fn modules(&self) -> Vec<u8> {
+        self.modules.clone()
+    }
+}
+
+/// Build the QR-Code matrix for a network, `auth_type` is the raw `T` value (e.g. `WPA`)
+#[wasm_bindgen]
+pub fn wifi_qr_matrix(
+    ssid: String,
+    auth_type: Option<String>,
+    password: Option<String>,
+    hidden: bool,
+) -> Result<QrMatrix, JsError> {
+    let code = Wifi::new(ssid)
+        .with_raw_auth_type(auth_type)
+        .with_password(password)
+        .with_hidden(hidden)
+        .qr_code()
+        .map_err(|err| JsError::new(&err.to_string()))?;
+
+    Ok(QrMatrix {
+        width: code.width(),
+        modules: code
+            .to_colors()
+            .into_iter()
+            .map(|color| (color == Color::Dark) as u8)
+            .collect(),
+    })
+}